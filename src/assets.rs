@@ -64,3 +64,53 @@ pub(crate) static ENCODING_TO_LANGUAGE: Lazy<HashMap<&'static str, Language>> =
         ("shift_jis", Language::Japanese),
     ])
 });
+
+// Single-byte code pages whose languages are not told apart by unicode ranges: DOS code
+// pages mix letters with box drawing and math symbols (f.e. Greek ones in ibm437)
+pub(crate) static CODE_PAGE_TO_LANGUAGES: Lazy<HashMap<&'static str, Vec<&'static Language>>> =
+    Lazy::new(|| {
+        HashMap::from_iter([
+            (
+                "ibm437",
+                vec![
+                    &Language::English,
+                    &Language::German,
+                    &Language::French,
+                    &Language::Spanish,
+                    &Language::Italian,
+                    &Language::Swedish,
+                    &Language::Finnish,
+                ],
+            ),
+            (
+                "ibm850",
+                vec![
+                    &Language::English,
+                    &Language::German,
+                    &Language::French,
+                    &Language::Dutch,
+                    &Language::Italian,
+                    &Language::Spanish,
+                    &Language::Portuguese,
+                    &Language::Swedish,
+                    &Language::Danish,
+                    &Language::Norwegian,
+                    &Language::Finnish,
+                ],
+            ),
+            ("ibm775", vec![&Language::Lithuanian, &Language::Estonian]),
+            (
+                "ibm852",
+                vec![
+                    &Language::Polish,
+                    &Language::Czech,
+                    &Language::Slovak,
+                    &Language::Hungarian,
+                    &Language::Croatian,
+                    &Language::Romanian,
+                    &Language::Slovene,
+                ],
+            ),
+            ("ibm865", vec![&Language::Norwegian, &Language::Danish]),
+        ])
+    });
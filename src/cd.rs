@@ -1,5 +1,8 @@
 #![allow(unused_variables)]
-use crate::assets::{ENCODING_TO_LANGUAGE, LANGUAGES, LANGUAGE_SUPPORTED_COUNT};
+use crate::assets::{
+    CODE_PAGE_TO_LANGUAGES, ENCODING_TO_LANGUAGE, LANGUAGES, LANGUAGE_SUPPORTED_COUNT,
+};
+use crate::codecs::{codec_from_label, registered_languages};
 use crate::consts::TOO_SMALL_SEQUENCE;
 use crate::entity::{CoherenceMatch, CoherenceMatches, Language};
 use crate::utils::{
@@ -9,9 +12,9 @@ use crate::utils::{
 use ahash::{HashMap, HashMapExt, HashSet};
 use cached::proc_macro::cached;
 use counter::Counter;
-use encoding::label::encoding_from_whatwg_label;
 use encoding::DecoderTrap;
 use ordered_float::OrderedFloat;
+use std::cmp::Reverse;
 use strsim::jaro;
 
 //
@@ -23,8 +26,8 @@ pub(crate) fn encoding_unicode_range(iana_name: &str) -> Result<Vec<&str>, Strin
     if is_multi_byte_encoding(iana_name) {
         return Err("Function not supported on multi-byte code page".to_string());
     }
-    let encoder = encoding_from_whatwg_label(iana_name)
-        .ok_or("No decoder found for this encoding".to_string())?;
+    let encoder =
+        codec_from_label(iana_name).ok_or("No decoder found for this encoding".to_string())?;
 
     let byte_range = 0x40..0xFF; // utf8 range. range.len()==191
     let mut result: HashMap<&str, u8> = HashMap::with_capacity(byte_range.len());
@@ -69,6 +72,12 @@ pub(crate) fn unicode_range_languages(primary_range: &str) -> Vec<&'static Langu
 // This function does the correspondence.
 #[cached(size = 128)]
 pub(crate) fn encoding_languages(iana_name: String) -> Vec<&'static Language> {
+    if let Some(languages) = registered_languages(&iana_name) {
+        return languages;
+    }
+    if let Some(languages) = CODE_PAGE_TO_LANGUAGES.get(iana_name.as_str()) {
+        return languages.clone();
+    }
     match encoding_unicode_range(&iana_name)
         .unwrap_or_default()
         .iter()
@@ -82,6 +91,9 @@ pub(crate) fn encoding_languages(iana_name: String) -> Vec<&'static Language> {
 // Multi-byte encoding language association. Some code page are heavily linked to particular language(s).
 // This function does the correspondence.
 pub(crate) fn mb_encoding_languages(iana_name: &str) -> Vec<&'static Language> {
+    if let Some(languages) = registered_languages(iana_name) {
+        return languages;
+    }
     ENCODING_TO_LANGUAGE
         .get(iana_name)
         .map_or(vec![], |found| vec![found])
@@ -119,7 +131,7 @@ pub(crate) fn alphabet_languages(
         }
     }
     // reverse sort
-    languages.sort_unstable_by(|&a, &b| b.1.cmp(&a.1));
+    languages.sort_unstable_by_key(|&(_, ratio)| Reverse(ratio));
     languages.iter().map(|&lang| lang.0).collect()
 }
 
@@ -188,7 +200,7 @@ pub(crate) fn merge_coherence_ratios(results: &[CoherenceMatches]) -> CoherenceM
         })
         .collect();
 
-    merge.sort_unstable_by(|a, b| b.score.cmp(&a.score));
+    merge.sort_unstable_by_key(|m| Reverse(m.score));
     merge
 }
 
@@ -249,6 +261,6 @@ pub(crate) fn coherence_ratio(
         }
     }
     results = filter_alt_coherence_matches(&results);
-    results.sort_unstable_by(|a, b| b.score.cmp(&a.score));
+    results.sort_unstable_by_key(|m| Reverse(m.score));
     Ok(results)
 }
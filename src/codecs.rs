@@ -0,0 +1,1170 @@
+use crate::consts::{IANA_SUPPORTED, IANA_SUPPORTED_SIMILAR};
+use crate::entity::Language;
+use crate::error::Error;
+use crate::utils::{codecs_similarity, is_multi_byte_encoding};
+use encoding::label::encoding_from_whatwg_label;
+use encoding::types::{
+    ByteWriter, CodecError, Encoding, EncodingRef, RawDecoder, RawEncoder, StringWriter,
+};
+use once_cell::sync::Lazy;
+use std::sync::{Arc, PoisonError, RwLock};
+
+mod tables;
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Codecs
+// Built-in codecs of encodings which `encoding` crate does not ship,
+// and registry of custom codecs provided by user
+/////////////////////////////////////////////////////////////////////////////////////
+
+// Built-in codecs with their labels (lowercase)
+static BUILTIN_CODECS: &[(EncodingRef, &[&str])] = &[
+    (
+        &UTF32Encoding::LittleEndian,
+        &["utf-32le", "utf32le", "ucs-4le"],
+    ),
+    // without BOM, utf-32 is big-endian (BOM is checked before any label is used)
+    (
+        &UTF32Encoding::BigEndian,
+        &["utf-32be", "utf-32", "utf32", "utf32be", "ucs-4", "ucs-4be"],
+    ),
+    (
+        &UTF7Encoding,
+        &["utf-7", "utf7", "unicode-1-1-utf-7", "csunicode11utf7"],
+    ),
+    (&UTF8VariantEncoding::Cesu8, &["cesu-8", "cesu8", "cscesu8"]),
+    (
+        &UTF8VariantEncoding::Modified,
+        &["mutf-8", "mutf8", "modified-utf-8", "java-modified-utf-8"],
+    ),
+    (&UTF8VariantEncoding::Wtf8, &["wtf-8", "wtf8"]),
+    (&IBM437, &["ibm437", "cp437", "437", "cspc8codepage437"]),
+    (&IBM737, &["ibm737", "cp737", "737", "x-ibm737"]),
+    (&IBM775, &["ibm775", "cp775", "775", "cspc775baltic"]),
+    (&IBM850, &["ibm850", "cp850", "850", "cspc850multilingual"]),
+    (&IBM852, &["ibm852", "cp852", "852", "cspcp852"]),
+    (&IBM865, &["ibm865", "cp865", "865", "csibm865"]),
+    (
+        &EBCDIC037,
+        &[
+            "ibm037",
+            "cp037",
+            "037",
+            "csibm037",
+            "ebcdic-cp-us",
+            "ebcdic-cp-ca",
+        ],
+    ),
+    (
+        &EBCDIC500,
+        &[
+            "ibm500",
+            "cp500",
+            "500",
+            "csibm500",
+            "ebcdic-cp-be",
+            "ebcdic-cp-ch",
+        ],
+    ),
+    (&EBCDIC1047, &["ibm1047", "cp1047", "1047", "csibm1047"]),
+    (
+        &EBCDIC1140,
+        &["ibm01140", "ibm1140", "cp1140", "ccsid01140", "csibm01140"],
+    ),
+];
+
+// Names of built-in codecs
+pub(crate) fn builtin_encodings() -> impl Iterator<Item = &'static str> {
+    BUILTIN_CODECS.iter().map(|(codec, _)| codec.name())
+}
+
+// Find codec by label: built-in codecs and WHATWG labels of `encoding` crate first, then
+// registered ones (their labels never overlap), so registry is locked only for custom labels.
+// Detector resolves codecs of its encodings once, detection does not look them up anymore.
+pub(crate) fn codec_from_label(label: &str) -> Option<EncodingRef> {
+    let label = label.trim().to_lowercase();
+    find_known_codec(&label).or_else(|| find_registered_codec(&registry(), &label))
+}
+
+fn find_known_codec(label: &str) -> Option<EncodingRef> {
+    BUILTIN_CODECS
+        .iter()
+        .find(|(_, labels)| labels.contains(&label))
+        .map(|&(codec, _)| codec)
+        .or_else(|| encoding_from_whatwg_label(label))
+}
+
+fn find_registered_codec(registry: &Registry, label: &str) -> Option<EncodingRef> {
+    registry
+        .codecs
+        .iter()
+        .find(|codec| codec.labels().any(|l| l == label))
+        .map(|&codec| codec as EncodingRef)
+}
+
+//
+// Registry of custom codecs
+//
+
+/// Codec of custom encoding (f.e. proprietary or legacy code page).
+///
+/// Once registered with [`register_codec`], encoding is supported as well as built-in ones:
+/// it is listed by [`supported_encodings`], its labels are resolved by `iana_name`, and it takes
+/// part in detection (with languages it is linked to, and single-byte encodings it is similar to).
+pub trait Codec: Send + Sync {
+    /// Name of encoding (lowercase), it is reported by `CharsetMatch::encoding`
+    fn name(&self) -> &'static str;
+    /// Other labels of encoding (lowercase)
+    fn aliases(&self) -> &[&'static str];
+    /// Characters may take more than one byte. Multi-byte encodings are decoded as a whole,
+    /// single-byte ones are compared to others (and may be decoded by chunks).
+    fn is_multibyte(&self) -> bool;
+    /// Decode bytes. Error is offset of the first byte which does not fit encoding.
+    fn decode(&self, bytes: &[u8]) -> Result<String, usize>;
+    /// Encode string. Error is (byte) offset of the first character which is not representable.
+    fn encode(&self, text: &str) -> Result<Vec<u8>, usize>;
+    /// Languages linked to encoding. If there are none (default), languages are inferred
+    /// from unicode ranges of single-byte encoding characters.
+    fn languages(&self) -> Vec<&'static Language> {
+        vec![]
+    }
+}
+
+/// Register custom codec. Detectors which are created afterwards probe its encoding too.
+/// Single-byte codec decodes every byte once, so it is compared to other single-byte encodings.
+///
+/// Fails if name or one of aliases is not lowercase, or is already a label of supported encoding.
+///
+/// ```rust
+/// use charset_normalizer_rs::codecs::{register_codec, supported_encodings, Codec};
+/// use charset_normalizer_rs::utils::iana_name;
+///
+/// // ASCII with bytes inverted
+/// struct InvertedAscii;
+///
+/// impl Codec for InvertedAscii {
+///     fn name(&self) -> &'static str {
+///         "x-inverted-ascii"
+///     }
+///     fn aliases(&self) -> &[&'static str] {
+///         &["x-inverted"]
+///     }
+///     fn is_multibyte(&self) -> bool {
+///         false
+///     }
+///     fn decode(&self, bytes: &[u8]) -> Result<String, usize> {
+///         match bytes.iter().position(|b| *b < 0x80) {
+///             Some(offset) => Err(offset),
+///             None => Ok(bytes.iter().map(|b| char::from(!b)).collect()),
+///         }
+///     }
+///     fn encode(&self, text: &str) -> Result<Vec<u8>, usize> {
+///         match text.char_indices().find(|(_, ch)| !ch.is_ascii()) {
+///             Some((offset, _)) => Err(offset),
+///             None => Ok(text.bytes().map(|b| !b).collect()),
+///         }
+///     }
+/// }
+///
+/// register_codec(InvertedAscii).unwrap();
+/// assert!(register_codec(InvertedAscii).is_err());
+/// assert!(supported_encodings().contains(&"x-inverted-ascii"));
+/// assert_eq!(iana_name("X-Inverted"), Some("x-inverted-ascii"));
+/// ```
+pub fn register_codec(codec: impl Codec + 'static) -> Result<(), Error> {
+    let mut custom = CustomEncoding {
+        codec: Arc::new(codec),
+        similar: vec![],
+    };
+    let labels: Vec<&'static str> = custom.labels().collect();
+    for (idx, &label) in labels.iter().enumerate() {
+        if label.is_empty() || label != label.trim().to_lowercase() {
+            return Err(Error::InvalidLabel(label.to_string()));
+        }
+        if labels[..idx].contains(&label) {
+            return Err(Error::DuplicateEncoding(label.to_string()));
+        }
+    }
+
+    // similarity is measured before registry is locked, as it resolves other codecs
+    if !custom.codec.is_multibyte() {
+        custom.similar = supported_encodings()
+            .into_iter()
+            .filter(|&enc| !is_multi_byte_encoding(enc))
+            .filter(|&enc| {
+                codec_from_label(enc)
+                    .is_some_and(|other| codecs_similarity(&custom, other) >= SIMILARITY_THRESHOLD)
+            })
+            .collect();
+    }
+
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(&label) = labels.iter().find(|label| {
+        find_known_codec(label)
+            .or_else(|| find_registered_codec(&registry, label))
+            .is_some()
+    }) {
+        return Err(Error::DuplicateEncoding(label.to_string()));
+    }
+    // codec is leaked, as registered codecs live until the end of program
+    registry.codecs.push(Box::leak(Box::new(custom)));
+    Ok(())
+}
+
+// Share of bytes decoded to the same characters, from which single-byte encodings are similar
+const SIMILARITY_THRESHOLD: f32 = 0.8;
+
+// Registered codecs
+struct Registry {
+    codecs: Vec<&'static CustomEncoding>,
+}
+
+static REGISTRY: Lazy<RwLock<Registry>> = Lazy::new(|| RwLock::new(Registry { codecs: vec![] }));
+
+fn registry() -> std::sync::RwLockReadGuard<'static, Registry> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Names of supported encodings: `IANA_SUPPORTED` (ones of `encoding` crate and built-in
+/// codecs) followed by encodings of codecs registered so far
+pub fn supported_encodings() -> Vec<&'static str> {
+    let registry = registry();
+    IANA_SUPPORTED
+        .iter()
+        .copied()
+        .chain(registry.codecs.iter().map(|codec| codec.codec.name()))
+        .collect()
+}
+
+/// Count of supported encodings, including codecs registered so far
+pub fn supported_encodings_count() -> usize {
+    IANA_SUPPORTED.len() + registry().codecs.len()
+}
+
+/// Single-byte encodings similar to given one: ones from `IANA_SUPPORTED_SIMILAR`,
+/// and similar ones of registered codecs (in both directions)
+pub fn similar_encodings(name: &str) -> Vec<&'static str> {
+    let registry = registry();
+    let mut similar = IANA_SUPPORTED_SIMILAR
+        .get(name)
+        .cloned()
+        .unwrap_or_default();
+    for codec in &registry.codecs {
+        if codec.codec.name() == name {
+            for &enc in &codec.similar {
+                if !similar.contains(&enc) {
+                    similar.push(enc);
+                }
+            }
+        } else if codec.similar.contains(&name) {
+            similar.push(codec.codec.name());
+        }
+    }
+    similar
+}
+
+// Registered custom codec
+fn registered_codec(name: &str) -> Option<&'static CustomEncoding> {
+    registry()
+        .codecs
+        .iter()
+        .find(|codec| codec.codec.name() == name)
+        .copied()
+}
+
+// Check if encoding of registered codec is a multi-byte one
+pub(crate) fn is_registered_multibyte(name: &str) -> bool {
+    registered_codec(name).is_some_and(|codec| codec.codec.is_multibyte())
+}
+
+// Languages linked to encoding of registered codec (None if there are no such ones)
+pub(crate) fn registered_languages(name: &str) -> Option<Vec<&'static Language>> {
+    Some(registered_codec(name)?.codec.languages()).filter(|languages| !languages.is_empty())
+}
+
+// Name and aliases of encoding of registered codec
+pub(crate) fn registered_aliases(name: &str) -> Option<Vec<&'static str>> {
+    registered_codec(name).map(|codec| codec.labels().collect())
+}
+
+// Custom codec as codec of `encoding` crate, so it is used like built-in ones
+struct CustomEncoding {
+    codec: Arc<dyn Codec>,
+    // supported single-byte encodings which were similar to it when it was registered
+    similar: Vec<&'static str>,
+}
+
+impl CustomEncoding {
+    fn labels(&self) -> impl Iterator<Item = &'static str> + '_ {
+        std::iter::once(self.codec.name()).chain(self.codec.aliases().iter().copied())
+    }
+}
+
+impl Encoding for CustomEncoding {
+    fn name(&self) -> &'static str {
+        self.codec.name()
+    }
+    fn raw_encoder(&self) -> Box<dyn RawEncoder> {
+        Box::new(CustomCoder(self.codec.clone()))
+    }
+    fn raw_decoder(&self) -> Box<dyn RawDecoder> {
+        Box::new(CustomCoder(self.codec.clone()))
+    }
+}
+
+// Custom codec is stateless, so the whole input is processed at once. In case of error
+// the part before it is processed again.
+struct CustomCoder(Arc<dyn Codec>);
+
+impl RawEncoder for CustomCoder {
+    fn from_self(&self) -> Box<dyn RawEncoder> {
+        Box::new(CustomCoder(self.0.clone()))
+    }
+    fn raw_feed(
+        &mut self,
+        input: &str,
+        output: &mut dyn ByteWriter,
+    ) -> (usize, Option<CodecError>) {
+        match self.0.encode(input) {
+            Ok(bytes) => {
+                output.write_bytes(&bytes);
+                (input.len(), None)
+            }
+            Err(offset) => {
+                let offset = offset.min(input.len());
+                if let Ok(bytes) = self.0.encode(&input[..offset]) {
+                    output.write_bytes(&bytes);
+                }
+                let char_len = input[offset..].chars().next().map_or(0, char::len_utf8);
+                (
+                    offset,
+                    Some(CodecError {
+                        upto: (offset + char_len) as isize,
+                        cause: "unrepresentable character".into(),
+                    }),
+                )
+            }
+        }
+    }
+    fn raw_finish(&mut self, _output: &mut dyn ByteWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+impl RawDecoder for CustomCoder {
+    fn from_self(&self) -> Box<dyn RawDecoder> {
+        Box::new(CustomCoder(self.0.clone()))
+    }
+    fn raw_feed(
+        &mut self,
+        input: &[u8],
+        output: &mut dyn StringWriter,
+    ) -> (usize, Option<CodecError>) {
+        match self.0.decode(input) {
+            Ok(text) => {
+                output.write_str(&text);
+                (input.len(), None)
+            }
+            Err(offset) => {
+                let offset = offset.min(input.len());
+                if let Ok(text) = self.0.decode(&input[..offset]) {
+                    output.write_str(&text);
+                }
+                (
+                    offset,
+                    Some(CodecError {
+                        upto: (offset + 1).min(input.len()) as isize,
+                        cause: "invalid sequence".into(),
+                    }),
+                )
+            }
+        }
+    }
+    fn raw_finish(&mut self, _output: &mut dyn StringWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+//
+// UTF-32 (every code point is a single 4-bytes unit)
+//
+
+#[derive(Clone, Copy)]
+pub(crate) enum UTF32Encoding {
+    LittleEndian,
+    BigEndian,
+}
+
+impl UTF32Encoding {
+    fn unit_from_bytes(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            UTF32Encoding::LittleEndian => u32::from_le_bytes(bytes),
+            UTF32Encoding::BigEndian => u32::from_be_bytes(bytes),
+        }
+    }
+
+    fn unit_to_bytes(self, unit: u32) -> [u8; 4] {
+        match self {
+            UTF32Encoding::LittleEndian => unit.to_le_bytes(),
+            UTF32Encoding::BigEndian => unit.to_be_bytes(),
+        }
+    }
+}
+
+impl Encoding for UTF32Encoding {
+    fn name(&self) -> &'static str {
+        match self {
+            UTF32Encoding::LittleEndian => "utf-32le",
+            UTF32Encoding::BigEndian => "utf-32be",
+        }
+    }
+    fn raw_encoder(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn raw_decoder(&self) -> Box<dyn RawDecoder> {
+        Box::new(UTF32Decoder {
+            encoding: *self,
+            pending: vec![],
+        })
+    }
+}
+
+impl RawEncoder for UTF32Encoding {
+    fn from_self(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn raw_feed(
+        &mut self,
+        input: &str,
+        output: &mut dyn ByteWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len() * 4);
+        for ch in input.chars() {
+            output.write_bytes(&self.unit_to_bytes(ch as u32));
+        }
+        (input.len(), None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn ByteWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+struct UTF32Decoder {
+    encoding: UTF32Encoding,
+    // bytes of incomplete unit from the end of previous input
+    pending: Vec<u8>,
+}
+
+impl RawDecoder for UTF32Decoder {
+    fn from_self(&self) -> Box<dyn RawDecoder> {
+        self.encoding.raw_decoder()
+    }
+    fn raw_feed(
+        &mut self,
+        input: &[u8],
+        output: &mut dyn StringWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len() / 4);
+
+        // complete unit started in previous input
+        let mut i = 0;
+        if !self.pending.is_empty() {
+            i = input.len().min(4 - self.pending.len());
+            self.pending.extend_from_slice(&input[..i]);
+            if self.pending.len() < 4 {
+                return (0, None);
+            }
+            let unit = self.encoding.unit_from_bytes([
+                self.pending[0],
+                self.pending[1],
+                self.pending[2],
+                self.pending[3],
+            ]);
+            self.pending.clear();
+            match char::from_u32(unit) {
+                Some(ch) => output.write_char(ch),
+                None => {
+                    return (
+                        0,
+                        Some(CodecError {
+                            upto: i as isize,
+                            cause: "invalid sequence".into(),
+                        }),
+                    )
+                }
+            }
+        }
+
+        while i + 4 <= input.len() {
+            let unit =
+                self.encoding
+                    .unit_from_bytes([input[i], input[i + 1], input[i + 2], input[i + 3]]);
+            match char::from_u32(unit) {
+                Some(ch) => output.write_char(ch),
+                None => {
+                    return (
+                        i,
+                        Some(CodecError {
+                            upto: i as isize + 4,
+                            cause: "invalid sequence".into(),
+                        }),
+                    )
+                }
+            }
+            i += 4;
+        }
+        self.pending.extend_from_slice(&input[i..]);
+        (i, None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn StringWriter) -> Option<CodecError> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        self.pending.clear();
+        Some(CodecError {
+            upto: 0,
+            cause: "incomplete sequence".into(),
+        })
+    }
+}
+
+//
+// UTF-7 (RFC 2152): ASCII characters go as is, others are written as base64 of UTF-16BE
+// in shift sequences which start with "+" and end with "-" (or any non-base64 character)
+//
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_value(byte: u8) -> Option<u32> {
+    match byte {
+        b'A'..=b'Z' => Some((byte - b'A') as u32),
+        b'a'..=b'z' => Some((byte - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((byte - b'0') as u32 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct UTF7Encoding;
+
+impl Encoding for UTF7Encoding {
+    fn name(&self) -> &'static str {
+        "utf-7"
+    }
+    fn raw_encoder(&self) -> Box<dyn RawEncoder> {
+        Box::new(UTF7Encoding)
+    }
+    fn raw_decoder(&self) -> Box<dyn RawDecoder> {
+        Box::new(UTF7Decoder::default())
+    }
+}
+
+impl UTF7Encoding {
+    // Characters written as is (direct and optional direct characters except "\\" and "~")
+    fn is_direct(ch: char) -> bool {
+        matches!(ch, ' '..='~' | '\t' | '\r' | '\n') && !matches!(ch, '+' | '\\' | '~')
+    }
+
+    // Write shift sequence for UTF-16 units, always terminated by "-"
+    fn write_shifted(units: &[u16], output: &mut dyn ByteWriter) {
+        output.write_byte(b'+');
+        let (mut bits, mut bit_count) = (0u32, 0u32);
+        for &unit in units {
+            bits = (bits << 16) | unit as u32;
+            bit_count += 16;
+            while bit_count >= 6 {
+                bit_count -= 6;
+                output.write_byte(BASE64_ALPHABET[((bits >> bit_count) & 0x3f) as usize]);
+            }
+        }
+        if bit_count > 0 {
+            output.write_byte(BASE64_ALPHABET[((bits << (6 - bit_count)) & 0x3f) as usize]);
+        }
+        output.write_byte(b'-');
+    }
+}
+
+impl RawEncoder for UTF7Encoding {
+    fn from_self(&self) -> Box<dyn RawEncoder> {
+        Box::new(UTF7Encoding)
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        true
+    }
+    fn raw_feed(
+        &mut self,
+        input: &str,
+        output: &mut dyn ByteWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+        let mut units: Vec<u16> = vec![];
+        for ch in input.chars() {
+            if UTF7Encoding::is_direct(ch) || ch == '+' {
+                if !units.is_empty() {
+                    UTF7Encoding::write_shifted(&units, output);
+                    units.clear();
+                }
+                match ch {
+                    '+' => output.write_bytes(b"+-"),
+                    _ => output.write_byte(ch as u8),
+                }
+            } else {
+                units.extend(ch.encode_utf16(&mut [0; 2]).iter());
+            }
+        }
+        if !units.is_empty() {
+            UTF7Encoding::write_shifted(&units, output);
+        }
+        (input.len(), None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn ByteWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+#[derive(Default)]
+struct UTF7Decoder {
+    // inside of shift sequence
+    shifted: bool,
+    // "+" was the last byte, so "-" means "+" itself
+    shift_just_started: bool,
+    // bits of base64 characters, which do not make a complete UTF-16 unit yet
+    bits: u32,
+    bit_count: u32,
+    high_surrogate: Option<u16>,
+    // characters of shift sequence, written only once it is complete and valid
+    pending: String,
+}
+
+impl UTF7Decoder {
+    // Leave shift sequence, the rest of bits must be zero padding
+    fn end_shift(&mut self, output: &mut dyn StringWriter) -> bool {
+        let is_valid = self.bit_count < 6
+            && self.bits == 0
+            && self.high_surrogate.is_none()
+            && !self.shift_just_started;
+        if is_valid {
+            output.write_str(&self.pending);
+        }
+        *self = UTF7Decoder::default();
+        is_valid
+    }
+}
+
+impl RawDecoder for UTF7Decoder {
+    fn from_self(&self) -> Box<dyn RawDecoder> {
+        Box::new(UTF7Decoder::default())
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        true
+    }
+    fn raw_feed(
+        &mut self,
+        input: &[u8],
+        output: &mut dyn StringWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+        let invalid = |upto: usize| CodecError {
+            upto: upto as isize,
+            cause: "invalid sequence".into(),
+        };
+
+        // bytes of unfinished shift sequence are not processed yet
+        let mut processed = 0;
+        for (i, &byte) in input.iter().enumerate() {
+            if self.shifted {
+                if let Some(value) = base64_value(byte) {
+                    self.shift_just_started = false;
+                    self.bits = (self.bits << 6) | value;
+                    self.bit_count += 6;
+                    if self.bit_count >= 16 {
+                        self.bit_count -= 16;
+                        let unit = (self.bits >> self.bit_count) as u16;
+                        self.bits &= (1 << self.bit_count) - 1;
+                        match (self.high_surrogate.take(), unit) {
+                            (None, 0xD800..=0xDBFF) => self.high_surrogate = Some(unit),
+                            (Some(high), 0xDC00..=0xDFFF) => {
+                                let code = 0x10000
+                                    + (((high as u32) - 0xD800) << 10)
+                                    + (unit as u32 - 0xDC00);
+                                self.pending.push(char::from_u32(code).unwrap_or_default());
+                            }
+                            (None, 0xDC00..=0xDFFF) | (Some(_), _) => {
+                                // bytes of the sequence are given to the trap instead
+                                self.pending.clear();
+                                return (processed, Some(invalid(i + 1)));
+                            }
+                            (None, unit) => self
+                                .pending
+                                .push(char::from_u32(unit as u32).unwrap_or_default()),
+                        }
+                    }
+                    continue;
+                }
+
+                // "+-" is "+" itself, otherwise shift sequence ends here
+                if byte == b'-' && self.shift_just_started {
+                    *self = UTF7Decoder::default();
+                    output.write_char('+');
+                    processed = i + 1;
+                    continue;
+                }
+                if !self.end_shift(output) {
+                    return (processed, Some(invalid(i)));
+                }
+                if byte == b'-' {
+                    processed = i + 1;
+                    continue;
+                }
+            }
+
+            match byte {
+                b'+' => {
+                    self.shifted = true;
+                    self.shift_just_started = true;
+                    processed = i;
+                }
+                0x80.. => return (i, Some(invalid(i + 1))),
+                _ => {
+                    output.write_char(byte as char);
+                    processed = i + 1;
+                }
+            }
+        }
+        (processed, None)
+    }
+    fn raw_finish(&mut self, output: &mut dyn StringWriter) -> Option<CodecError> {
+        if !self.shifted || self.end_shift(output) {
+            return None;
+        }
+        Some(CodecError {
+            upto: 0,
+            cause: "incomplete sequence".into(),
+        })
+    }
+}
+
+//
+// UTF-8 variants: CESU-8 (supplementary characters as two 3-bytes surrogates), Modified UTF-8
+// (CESU-8 with NUL as overlong "C0 80", written by Java) and WTF-8 (UTF-8 with lone surrogates,
+// used for Windows file names). Lone surrogates have no Unicode scalar value, so WTF-8 decodes
+// them as U+FFFD.
+//
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum UTF8VariantEncoding {
+    Cesu8,
+    Modified,
+    Wtf8,
+}
+
+// Result of decoding of a single sequence
+enum Utf8Step {
+    Char(char, usize),
+    Incomplete,
+    Invalid(usize),
+}
+
+impl UTF8VariantEncoding {
+    // Decode the first sequence of bytes
+    fn decode_sequence(self, bytes: &[u8]) -> Utf8Step {
+        let continuation = |pos: usize, range: std::ops::RangeInclusive<u8>| match bytes.get(pos) {
+            Some(b) if range.contains(b) => Some(Some((b & 0x3f) as u32)),
+            Some(_) => Some(None),
+            None => None,
+        };
+        let lead = bytes[0] as u32;
+        let (len, second_range) = match bytes[0] {
+            0x00 if self == UTF8VariantEncoding::Modified => return Utf8Step::Invalid(1),
+            0x00..=0x7f => return Utf8Step::Char(bytes[0] as char, 1),
+            0xc0 if self == UTF8VariantEncoding::Modified => (2, 0x80..=0x80),
+            0xc2..=0xdf => (2, 0x80..=0xbf),
+            0xe0 => (3, 0xa0..=0xbf),
+            0xed => (3, 0x80..=0xbf),
+            0xe1..=0xef => (3, 0x80..=0xbf),
+            0xf0 if self == UTF8VariantEncoding::Wtf8 => (4, 0x90..=0xbf),
+            0xf1..=0xf3 if self == UTF8VariantEncoding::Wtf8 => (4, 0x80..=0xbf),
+            0xf4 if self == UTF8VariantEncoding::Wtf8 => (4, 0x80..=0x8f),
+            _ => return Utf8Step::Invalid(1),
+        };
+        let mut code = lead & (0x7f >> len);
+        for pos in 1..len {
+            let range = match pos {
+                1 => second_range.clone(),
+                _ => 0x80..=0xbf,
+            };
+            match continuation(pos, range) {
+                Some(Some(bits)) => code = (code << 6) | bits,
+                Some(None) => return Utf8Step::Invalid(pos),
+                None => return Utf8Step::Incomplete,
+            }
+        }
+        if !(0xd800..=0xdfff).contains(&code) {
+            return Utf8Step::Char(char::from_u32(code).unwrap_or_default(), len);
+        }
+
+        // surrogate: the high one must be followed by the low one in CESU-8 & Modified UTF-8,
+        // while WTF-8 allows lone surrogates only
+        let low = match bytes.get(3..6) {
+            _ if code >= 0xdc00 => None,
+            Some(&[0xed, second @ 0xb0..=0xbf, third @ 0x80..=0xbf]) => {
+                Some(0xdc00 + (((second & 0x0f) as u32) << 6) + (third & 0x3f) as u32)
+            }
+            Some(_) => None,
+            // the rest of pair may come later
+            None => match &bytes[3..] {
+                [] | [0xed] | [0xed, 0xb0..=0xbf] => return Utf8Step::Incomplete,
+                _ => None,
+            },
+        };
+        match (self, low) {
+            (UTF8VariantEncoding::Wtf8, None) => Utf8Step::Char(char::REPLACEMENT_CHARACTER, 3),
+            (UTF8VariantEncoding::Wtf8, Some(_)) | (_, None) => Utf8Step::Invalid(3),
+            (_, Some(low)) => {
+                let code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                Utf8Step::Char(char::from_u32(code).unwrap_or_default(), 6)
+            }
+        }
+    }
+}
+
+impl Encoding for UTF8VariantEncoding {
+    fn name(&self) -> &'static str {
+        match self {
+            UTF8VariantEncoding::Cesu8 => "cesu-8",
+            UTF8VariantEncoding::Modified => "mutf-8",
+            UTF8VariantEncoding::Wtf8 => "wtf-8",
+        }
+    }
+    fn raw_encoder(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn raw_decoder(&self) -> Box<dyn RawDecoder> {
+        Box::new(UTF8VariantDecoder {
+            encoding: *self,
+            pending: vec![],
+        })
+    }
+}
+
+impl RawEncoder for UTF8VariantEncoding {
+    fn from_self(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        true
+    }
+    fn raw_feed(
+        &mut self,
+        input: &str,
+        output: &mut dyn ByteWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+        let mut buffer = [0u8; 4];
+        for ch in input.chars() {
+            match (*self, ch) {
+                (UTF8VariantEncoding::Modified, '\0') => output.write_bytes(b"\xc0\x80"),
+                (UTF8VariantEncoding::Cesu8 | UTF8VariantEncoding::Modified, '\u{10000}'..) => {
+                    for unit in ch.encode_utf16(&mut [0; 2]) {
+                        let unit = *unit as u32;
+                        output.write_bytes(&[
+                            0xe0 | (unit >> 12) as u8,
+                            0x80 | ((unit >> 6) & 0x3f) as u8,
+                            0x80 | (unit & 0x3f) as u8,
+                        ]);
+                    }
+                }
+                _ => output.write_bytes(ch.encode_utf8(&mut buffer).as_bytes()),
+            }
+        }
+        (input.len(), None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn ByteWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+struct UTF8VariantDecoder {
+    encoding: UTF8VariantEncoding,
+    // bytes of incomplete sequence from the end of previous input
+    pending: Vec<u8>,
+}
+
+impl RawDecoder for UTF8VariantDecoder {
+    fn from_self(&self) -> Box<dyn RawDecoder> {
+        self.encoding.raw_decoder()
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        true
+    }
+    fn raw_feed(
+        &mut self,
+        input: &[u8],
+        output: &mut dyn StringWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+
+        // offsets in data are shifted by pending bytes against offsets in input
+        let pending_len = self.pending.len();
+        let data: Vec<u8> = self
+            .pending
+            .drain(..)
+            .chain(input.iter().copied())
+            .collect();
+        let mut i = 0;
+        while i < data.len() {
+            match self.encoding.decode_sequence(&data[i..]) {
+                Utf8Step::Char(ch, len) => {
+                    output.write_char(ch);
+                    i += len;
+                }
+                Utf8Step::Incomplete => {
+                    self.pending.extend_from_slice(&data[i..]);
+                    break;
+                }
+                Utf8Step::Invalid(len) => {
+                    return (
+                        i.saturating_sub(pending_len),
+                        Some(CodecError {
+                            upto: (i + len) as isize - pending_len as isize,
+                            cause: "invalid sequence".into(),
+                        }),
+                    )
+                }
+            }
+        }
+        (i.saturating_sub(pending_len), None)
+    }
+    fn raw_finish(&mut self, output: &mut dyn StringWriter) -> Option<CodecError> {
+        // lone high surrogate at the very end is valid WTF-8
+        if self.encoding == UTF8VariantEncoding::Wtf8
+            && matches!(self.pending[..], [0xed, 0xa0..=0xaf, _])
+        {
+            output.write_char(char::REPLACEMENT_CHARACTER);
+            self.pending.clear();
+        }
+        if self.pending.is_empty() {
+            return None;
+        }
+        self.pending.clear();
+        Some(CodecError {
+            upto: 0,
+            cause: "incomplete sequence".into(),
+        })
+    }
+}
+
+//
+// Table-driven single-byte code pages: lower half is ASCII, upper half comes from table
+//
+
+#[derive(Clone, Copy)]
+pub(crate) struct SingleByteEncoding {
+    name: &'static str,
+    table: &'static [char; 128],
+}
+
+// DOS (OEM) code pages of IBM PC
+static IBM437: SingleByteEncoding = SingleByteEncoding {
+    name: "ibm437",
+    table: &tables::IBM437,
+};
+static IBM737: SingleByteEncoding = SingleByteEncoding {
+    name: "ibm737",
+    table: &tables::IBM737,
+};
+static IBM775: SingleByteEncoding = SingleByteEncoding {
+    name: "ibm775",
+    table: &tables::IBM775,
+};
+static IBM850: SingleByteEncoding = SingleByteEncoding {
+    name: "ibm850",
+    table: &tables::IBM850,
+};
+static IBM852: SingleByteEncoding = SingleByteEncoding {
+    name: "ibm852",
+    table: &tables::IBM852,
+};
+static IBM865: SingleByteEncoding = SingleByteEncoding {
+    name: "ibm865",
+    table: &tables::IBM865,
+};
+
+impl Encoding for SingleByteEncoding {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn raw_encoder(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn raw_decoder(&self) -> Box<dyn RawDecoder> {
+        Box::new(*self)
+    }
+}
+
+impl RawEncoder for SingleByteEncoding {
+    fn from_self(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        true
+    }
+    fn raw_feed(
+        &mut self,
+        input: &str,
+        output: &mut dyn ByteWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+        for (i, ch) in input.char_indices() {
+            if ch.is_ascii() {
+                output.write_byte(ch as u8);
+            } else if let Some(pos) = self.table.iter().position(|&c| c == ch) {
+                output.write_byte(0x80 + pos as u8);
+            } else {
+                return (
+                    i,
+                    Some(CodecError {
+                        upto: (i + ch.len_utf8()) as isize,
+                        cause: "unrepresentable character".into(),
+                    }),
+                );
+            }
+        }
+        (input.len(), None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn ByteWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+impl RawDecoder for SingleByteEncoding {
+    fn from_self(&self) -> Box<dyn RawDecoder> {
+        Box::new(*self)
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        true
+    }
+    fn raw_feed(
+        &mut self,
+        input: &[u8],
+        output: &mut dyn StringWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+        for &byte in input {
+            match byte {
+                0x00..=0x7f => output.write_char(byte as char),
+                _ => output.write_char(self.table[(byte - 0x80) as usize]),
+            }
+        }
+        (input.len(), None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn StringWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+//
+// EBCDIC code pages of mainframes: the whole table is used, there is no ASCII half.
+// Both newline conventions are decoded as LF: NEL (0x15) of datasets and LF (0x25)
+// of Unix System Services files, LF is encoded back as 0x25.
+//
+
+const EBCDIC_NEL: u8 = 0x15;
+
+#[derive(Clone, Copy)]
+pub(crate) struct EbcdicEncoding {
+    name: &'static str,
+    table: &'static [char; 256],
+}
+
+static EBCDIC037: EbcdicEncoding = EbcdicEncoding {
+    name: "ibm037",
+    table: &tables::EBCDIC037,
+};
+static EBCDIC500: EbcdicEncoding = EbcdicEncoding {
+    name: "ibm500",
+    table: &tables::EBCDIC500,
+};
+static EBCDIC1047: EbcdicEncoding = EbcdicEncoding {
+    name: "ibm1047",
+    table: &tables::EBCDIC1047,
+};
+static EBCDIC1140: EbcdicEncoding = EbcdicEncoding {
+    name: "ibm01140",
+    table: &tables::EBCDIC1140,
+};
+
+impl Encoding for EbcdicEncoding {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn raw_encoder(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn raw_decoder(&self) -> Box<dyn RawDecoder> {
+        Box::new(*self)
+    }
+}
+
+impl RawEncoder for EbcdicEncoding {
+    fn from_self(&self) -> Box<dyn RawEncoder> {
+        Box::new(*self)
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        false
+    }
+    fn raw_feed(
+        &mut self,
+        input: &str,
+        output: &mut dyn ByteWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+        for (i, ch) in input.char_indices() {
+            match self.table.iter().position(|&c| c == ch) {
+                Some(pos) => output.write_byte(pos as u8),
+                None => {
+                    return (
+                        i,
+                        Some(CodecError {
+                            upto: (i + ch.len_utf8()) as isize,
+                            cause: "unrepresentable character".into(),
+                        }),
+                    )
+                }
+            }
+        }
+        (input.len(), None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn ByteWriter) -> Option<CodecError> {
+        None
+    }
+}
+
+impl RawDecoder for EbcdicEncoding {
+    fn from_self(&self) -> Box<dyn RawDecoder> {
+        Box::new(*self)
+    }
+    fn is_ascii_compatible(&self) -> bool {
+        false
+    }
+    fn raw_feed(
+        &mut self,
+        input: &[u8],
+        output: &mut dyn StringWriter,
+    ) -> (usize, Option<CodecError>) {
+        output.writer_hint(input.len());
+        for &byte in input {
+            match byte {
+                EBCDIC_NEL => output.write_char('\n'),
+                _ => output.write_char(self.table[byte as usize]),
+            }
+        }
+        (input.len(), None)
+    }
+    fn raw_finish(&mut self, _output: &mut dyn StringWriter) -> Option<CodecError> {
+        None
+    }
+}
@@ -1,13 +1,18 @@
+use crate::codecs::builtin_encodings;
 use ahash::{HashMap, HashSet};
 use core::ops::RangeInclusive;
 use encoding::all::encodings;
 use once_cell::sync::Lazy;
-use regex::Regex;
+use regex::bytes::Regex;
 
 pub static TOO_BIG_SEQUENCE: usize = 1_000_000; // 10E6
-pub(crate) static MAX_PROCESSED_BYTES: usize = 500_000;
+/// Amount of bytes analysed by mess & coherence probing (also default read budget of readers)
+pub static MAX_PROCESSED_BYTES: usize = 500_000;
 pub(crate) static TOO_SMALL_SEQUENCE: usize = 32;
 pub(crate) static UTF8_MAXIMAL_ALLOCATION: usize = 1_112_064;
+pub(crate) static UTF16_SNIFF_SIZE: usize = 8192;
+pub(crate) static EBCDIC_SNIFF_SIZE: usize = 8192;
+pub(crate) static OEM_SNIFF_SIZE: usize = 8192;
 pub(crate) static COMMON_SAFE_ASCII_CHARACTERS: &str = "<>=:/&;{}[],|\"-";
 
 // Contain for each eligible encoding a list of/item bytes SIG/BOM
@@ -17,6 +22,8 @@ pub(crate) static ENCODING_MARKS: Lazy<HashMap<&str, &[u8]>> = Lazy::new(|| {
         ("gb18030", b"\x84\x31\x95\x33".as_slice()),
         ("utf-16le", b"\xff\xfe".as_slice()),
         ("utf-16be", b"\xfe\xff".as_slice()),
+        ("utf-32le", b"\xff\xfe\x00\x00".as_slice()),
+        ("utf-32be", b"\x00\x00\xfe\xff".as_slice()),
     ])
 });
 
@@ -328,20 +335,67 @@ pub(crate) static UNICODE_SECONDARY_RANGE_KEYWORD: Lazy<HashSet<&'static str>> =
     ])
 });
 
-pub(crate) static RE_POSSIBLE_ENCODING_INDICATION: Lazy<Regex> = Lazy::new(|| {
+// Encoding declarations, the first group is always declared label
+pub(crate) static RE_XML_DECLARATION: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-    r#"(?:(?:encoding)|(?:charset)|(?:coding))(?:[\:= ]{1,10})(?:["']?)([a-zA-Z0-9\-_]+)(?:["']?)"#
-).unwrap()
+        r#"<\?xml\s+version\s*=\s*["'][^"']*["']\s+encoding\s*=\s*["']([A-Za-z][\w.\-]*)["']"#,
+    )
+    .unwrap()
 });
+pub(crate) static RE_CSS_CHARSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"@charset\s+["']([\w.:\-]+)["']\s*;"#).unwrap());
+pub(crate) static RE_HTML_META: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<meta\s[^<>]*").unwrap());
+pub(crate) static RE_HTML_META_CHARSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\bcharset\s*=\s*["']?\s*([\w.:\-]+)"#).unwrap());
+pub(crate) static RE_MIME_CONTENT_TYPE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?im)^content-type:[ \t]*[^\r\n;]+;[^\r\n]*?\bcharset\s*=\s*["']?([\w.:\-]+)"#)
+        .unwrap()
+});
+pub(crate) static RE_SQL_SET_NAMES: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?im)^[ \t]*set[ \t]+names[ \t]+["'`]?([\w\-]+)["'`]?"#).unwrap());
+pub(crate) static RE_EMACS_MODELINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"-\*-[^\r\n]*?\bcoding:[ \t]*([\w.\-]+)[^\r\n]*?-\*-").unwrap());
+pub(crate) static RE_VIM_MODELINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:vi|vim|ex):[^\r\n]*?\b(?:fenc|fileencoding)=([\w.\-]+)").unwrap()
+});
+pub(crate) static RE_PEP263: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^[ \t\f]*#[^\r\n]*?coding[:=][ \t]*([\w.\-]+)").unwrap());
+
+// Labels used in declarations (MySQL, Python, Emacs, ...), which are not WHATWG labels
+pub(crate) static DECLARATION_ALIASES: Lazy<HashMap<&'static str, &'static str>> =
+    Lazy::new(|| {
+        HashMap::from_iter([
+            ("utf8mb3", "utf-8"),
+            ("utf8mb4", "utf-8"),
+            ("latin-1", "windows-1252"),
+            ("koi8r", "koi8-r"),
+            ("koi8u", "koi8-u"),
+            ("sjis", "shift_jis"),
+            ("cp932", "shift_jis"),
+            ("ujis", "euc-jp"),
+            ("eucjpms", "euc-jp"),
+            ("euckr", "euc-kr"),
+            ("cp949", "euc-kr"),
+            ("cp936", "gbk"),
+            ("cp950", "big5"),
+            ("big5hkscs", "big5"),
+        ])
+    });
 
+/// Names of encodings supported out of the box: ones of `encoding` crate and built-in codecs.
+/// Codecs registered with `register_codec` are listed by `codecs::supported_encodings` too.
 pub static IANA_SUPPORTED: Lazy<Vec<&'static str>> = Lazy::new(|| {
     encodings()
         .iter()
         .filter(|&enc| !["error", "encoder-only-utf-8", "pua-mapped-binary"].contains(&enc.name()))
         .map(|&enc| enc.whatwg_name().unwrap_or(enc.name()))
+        .chain(builtin_encodings())
         .collect()
 });
 
+/// Count of encodings supported out of the box (see `codecs::supported_encodings_count`
+/// for count with registered codecs)
 pub static IANA_SUPPORTED_COUNT: Lazy<usize> = Lazy::new(|| IANA_SUPPORTED.len());
 
 // chardet encoding names (in lowercase!)
@@ -652,6 +706,57 @@ pub static IANA_SUPPORTED_ALIASES: Lazy<HashMap<&'static str, Vec<&'static str>>
                 ],
             ),
             ("x-user-defined", vec!["x-user-defined"]),
+            ("utf-32le", vec!["ucs-4le", "utf-32le", "utf32le"]),
+            (
+                "utf-32be",
+                vec!["ucs-4", "ucs-4be", "utf-32", "utf-32be", "utf32", "utf32be"],
+            ),
+            (
+                "utf-7",
+                vec!["csunicode11utf7", "unicode-1-1-utf-7", "utf-7", "utf7"],
+            ),
+            ("cesu-8", vec!["cesu-8", "cesu8", "cscesu8"]),
+            (
+                "mutf-8",
+                vec!["java-modified-utf-8", "modified-utf-8", "mutf-8", "mutf8"],
+            ),
+            ("wtf-8", vec!["wtf-8", "wtf8"]),
+            ("ibm437", vec!["437", "cp437", "cspc8codepage437", "ibm437"]),
+            ("ibm737", vec!["737", "cp737", "ibm737", "x-ibm737"]),
+            ("ibm775", vec!["775", "cp775", "cspc775baltic", "ibm775"]),
+            (
+                "ibm850",
+                vec!["850", "cp850", "cspc850multilingual", "ibm850"],
+            ),
+            ("ibm852", vec!["852", "cp852", "cspcp852", "ibm852"]),
+            ("ibm865", vec!["865", "cp865", "csibm865", "ibm865"]),
+            (
+                "ibm037",
+                vec![
+                    "037",
+                    "cp037",
+                    "csibm037",
+                    "ebcdic-cp-ca",
+                    "ebcdic-cp-us",
+                    "ibm037",
+                ],
+            ),
+            (
+                "ibm500",
+                vec![
+                    "500",
+                    "cp500",
+                    "csibm500",
+                    "ebcdic-cp-be",
+                    "ebcdic-cp-ch",
+                    "ibm500",
+                ],
+            ),
+            ("ibm1047", vec!["1047", "cp1047", "csibm1047", "ibm1047"]),
+            (
+                "ibm01140",
+                vec!["ccsid01140", "cp1140", "csibm01140", "ibm01140", "ibm1140"],
+            ),
         ])
     });
 
@@ -660,6 +765,13 @@ pub static IANA_SUPPORTED_SIMILAR: Lazy<HashMap<&'static str, Vec<&'static str>>
         HashMap::from_iter([
             ("windows-1252", vec!["iso-8859-15", "windows-1254"]),
             ("windows-1253", vec!["iso-8859-7"]),
+            ("ibm437", vec!["ibm850", "ibm865"]),
+            ("ibm850", vec!["ibm437", "ibm865"]),
+            ("ibm865", vec!["ibm437", "ibm850"]),
+            ("ibm037", vec!["ibm01140", "ibm1047", "ibm500"]),
+            ("ibm01140", vec!["ibm037", "ibm1047", "ibm500"]),
+            ("ibm1047", vec!["ibm01140", "ibm037", "ibm500"]),
+            ("ibm500", vec!["ibm01140", "ibm037", "ibm1047"]),
             ("windows-1254", vec!["iso-8859-15", "windows-1252"]),
             ("windows-1257", vec!["iso-8859-13"]),
             (
@@ -750,3 +862,148 @@ pub static IANA_SUPPORTED_SIMILAR: Lazy<HashMap<&'static str, Vec<&'static str>>
             ),
         ])
     });
+
+// Weights of encodings hinted by caller's context (explicit priors have own weights)
+pub(crate) static CONTENT_TYPE_PRIOR_WEIGHT: f32 = 1.0;
+pub(crate) static LOCALE_PRIOR_WEIGHT: f32 = 0.3;
+pub(crate) static TLD_PRIOR_WEIGHT: f32 = 0.2;
+
+// Legacy encodings commonly used for languages (ISO 639-1 code or language_territory), most likely first
+pub(crate) static LOCALE_ENCODINGS: Lazy<HashMap<&'static str, Vec<&'static str>>> =
+    Lazy::new(|| {
+        HashMap::from_iter([
+            ("ar", vec!["windows-1256", "iso-8859-6"]),
+            ("be", vec!["windows-1251", "koi8-r"]),
+            ("bg", vec!["windows-1251", "iso-8859-5"]),
+            ("cs", vec!["windows-1250", "iso-8859-2"]),
+            ("da", vec!["windows-1252", "iso-8859-15"]),
+            ("de", vec!["windows-1252", "iso-8859-15"]),
+            ("el", vec!["windows-1253", "iso-8859-7"]),
+            ("en", vec!["windows-1252"]),
+            ("es", vec!["windows-1252", "iso-8859-15"]),
+            ("et", vec!["windows-1257", "iso-8859-13"]),
+            ("fa", vec!["windows-1256"]),
+            ("fi", vec!["windows-1252", "iso-8859-15"]),
+            ("fr", vec!["windows-1252", "iso-8859-15"]),
+            ("he", vec!["windows-1255", "iso-8859-8"]),
+            ("hr", vec!["windows-1250", "iso-8859-2"]),
+            ("hu", vec!["windows-1250", "iso-8859-2"]),
+            ("it", vec!["windows-1252", "iso-8859-15"]),
+            ("ja", vec!["shift_jis", "euc-jp", "iso-2022-jp"]),
+            ("kk", vec!["windows-1251"]),
+            ("ko", vec!["euc-kr"]),
+            ("lt", vec!["windows-1257", "iso-8859-13"]),
+            ("lv", vec!["windows-1257", "iso-8859-13"]),
+            ("mk", vec!["windows-1251", "iso-8859-5"]),
+            ("nl", vec!["windows-1252", "iso-8859-15"]),
+            ("no", vec!["windows-1252", "iso-8859-15"]),
+            ("pl", vec!["windows-1250", "iso-8859-2"]),
+            ("pt", vec!["windows-1252", "iso-8859-15"]),
+            ("ro", vec!["windows-1250", "iso-8859-16"]),
+            ("ru", vec!["windows-1251", "koi8-r", "ibm866"]),
+            ("sk", vec!["windows-1250", "iso-8859-2"]),
+            ("sl", vec!["windows-1250", "iso-8859-2"]),
+            ("sr", vec!["windows-1251", "windows-1250"]),
+            ("sv", vec!["windows-1252", "iso-8859-15"]),
+            ("th", vec!["windows-874"]),
+            ("tr", vec!["windows-1254"]),
+            ("uk", vec!["windows-1251", "koi8-u"]),
+            ("vi", vec!["windows-1258"]),
+            ("zh", vec!["gbk", "gb18030", "big5"]),
+            ("zh_cn", vec!["gbk", "gb18030"]),
+            ("zh_hk", vec!["big5"]),
+            ("zh_tw", vec!["big5"]),
+        ])
+    });
+
+// Locales of country code top-level domains
+pub(crate) static TLD_LOCALES: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    HashMap::from_iter([
+        ("at", "de"),
+        ("be", "nl"),
+        ("bg", "bg"),
+        ("br", "pt"),
+        ("by", "be"),
+        ("ch", "de"),
+        ("cn", "zh_cn"),
+        ("cz", "cs"),
+        ("de", "de"),
+        ("dk", "da"),
+        ("ee", "et"),
+        ("eg", "ar"),
+        ("es", "es"),
+        ("fi", "fi"),
+        ("fr", "fr"),
+        ("gr", "el"),
+        ("hk", "zh_hk"),
+        ("hr", "hr"),
+        ("hu", "hu"),
+        ("il", "he"),
+        ("ir", "fa"),
+        ("it", "it"),
+        ("jp", "ja"),
+        ("kr", "ko"),
+        ("kz", "kk"),
+        ("lt", "lt"),
+        ("lv", "lv"),
+        ("mk", "mk"),
+        ("nl", "nl"),
+        ("no", "no"),
+        ("pl", "pl"),
+        ("pt", "pt"),
+        ("ro", "ro"),
+        ("rs", "sr"),
+        ("ru", "ru"),
+        ("sa", "ar"),
+        ("se", "sv"),
+        ("si", "sl"),
+        ("sk", "sk"),
+        ("th", "th"),
+        ("tr", "tr"),
+        ("tw", "zh_tw"),
+        ("ua", "uk"),
+        ("uk", "en"),
+        ("vn", "vi"),
+    ])
+});
+
+// Named character references resolved when markup is stripped (the most common ones)
+pub(crate) static HTML_ENTITIES: Lazy<HashMap<&'static str, char>> = Lazy::new(|| {
+    HashMap::from_iter([
+        ("amp", '&'),
+        ("lt", '<'),
+        ("gt", '>'),
+        ("quot", '"'),
+        ("apos", '\''),
+        ("nbsp", '\u{a0}'),
+        ("copy", '©'),
+        ("reg", '®'),
+        ("trade", '™'),
+        ("deg", '°'),
+        ("plusmn", '±'),
+        ("times", '×'),
+        ("divide", '÷'),
+        ("sect", '§'),
+        ("para", '¶'),
+        ("middot", '·'),
+        ("bull", '•'),
+        ("hellip", '…'),
+        ("ndash", '–'),
+        ("mdash", '—'),
+        ("lsquo", '‘'),
+        ("rsquo", '’'),
+        ("sbquo", '‚'),
+        ("ldquo", '“'),
+        ("rdquo", '”'),
+        ("bdquo", '„'),
+        ("laquo", '«'),
+        ("raquo", '»'),
+        ("euro", '€'),
+        ("pound", '£'),
+        ("yen", '¥'),
+        ("cent", '¢'),
+        ("iexcl", '¡'),
+        ("iquest", '¿'),
+        ("shy", '\u{ad}'),
+    ])
+});
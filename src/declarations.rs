@@ -0,0 +1,211 @@
+use crate::codecs::supported_encodings;
+use crate::consts::{
+    DECLARATION_ALIASES, RE_CSS_CHARSET, RE_EMACS_MODELINE, RE_HTML_META, RE_HTML_META_CHARSET,
+    RE_MIME_CONTENT_TYPE, RE_PEP263, RE_SQL_SET_NAMES, RE_VIM_MODELINE, RE_XML_DECLARATION,
+};
+use crate::utils::{find_subsequence, iana_name};
+use regex::bytes::Regex;
+use std::ops::Range;
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Declarations
+// Encoding declarations written in content itself (markup, source code, headers)
+/////////////////////////////////////////////////////////////////////////////////////
+
+/// Syntax in which encoding was declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclarationSyntax {
+    /// `<meta charset="...">`
+    HtmlMetaCharset,
+    /// `<meta http-equiv="Content-Type" content="text/html; charset=...">`
+    HtmlHttpEquiv,
+    /// `<?xml version="1.0" encoding="..."?>`
+    XmlDeclaration,
+    /// `@charset "...";`
+    CssCharset,
+    /// `# -*- coding: ... -*-` or `# coding=...` (Python PEP 263)
+    Pep263,
+    /// `-*- coding: ... -*-`
+    EmacsModeline,
+    /// `vim: set fenc=... :`
+    VimModeline,
+    /// `SET NAMES ...`
+    SqlSetNames,
+    /// `Content-Type: text/plain; charset=...` header
+    MimeContentType,
+}
+
+/// Encoding declaration found in content
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    /// Label as it is written in content
+    pub label: String,
+    /// IANA name of declared encoding (None if label is unknown)
+    pub encoding: Option<&'static str>,
+    /// Byte span of the whole declaration
+    pub span: Range<usize>,
+    /// Syntax which matched
+    pub syntax: DeclarationSyntax,
+    /// Declaration is placed where its syntax expects it (f.e. XML declaration at the very
+    /// beginning, PEP 263 comment on one of the first two lines), so it is hardly a quotation
+    pub unambiguous: bool,
+}
+
+/// Find encoding declarations in the first `search_zone` bytes, in order of appearance.
+///
+/// ```rust
+/// use charset_normalizer_rs::declarations::{find_declarations, DeclarationSyntax};
+///
+/// let declarations = find_declarations(b"<?xml version=\"1.0\" encoding=\"EUC-JP\"?>", 4096);
+/// assert_eq!(declarations[0].syntax, DeclarationSyntax::XmlDeclaration);
+/// assert_eq!(declarations[0].encoding, Some("euc-jp"));
+/// assert_eq!(declarations[0].span, 0..37);
+/// ```
+pub fn find_declarations(sequence: &[u8], search_zone: usize) -> Vec<Declaration> {
+    let sequence = &sequence[..search_zone.min(sequence.len())];
+    let mut declarations: Vec<Declaration> = vec![];
+
+    // the more specific syntax goes first, as the same declaration may fit several ones
+    // (f.e. Emacs modeline is also PEP 263 comment)
+    let mut found = |syntax: DeclarationSyntax, span: Range<usize>, label: &[u8]| {
+        if declarations
+            .iter()
+            .any(|d| d.span.start < span.end && span.start < d.span.end)
+        {
+            return;
+        }
+        let label = String::from_utf8_lossy(label).to_string();
+        declarations.push(Declaration {
+            encoding: declared_encoding(&label),
+            unambiguous: is_unambiguous(sequence, syntax, &span),
+            label,
+            span,
+            syntax,
+        });
+    };
+
+    for (syntax, regex) in [
+        (DeclarationSyntax::XmlDeclaration, &*RE_XML_DECLARATION),
+        (DeclarationSyntax::CssCharset, &*RE_CSS_CHARSET),
+    ] {
+        for_each_label(regex, sequence, |span, label| found(syntax, span, label));
+    }
+
+    // html meta tags: charset is either attribute of its own or a part of http-equiv content,
+    // commented out tags are skipped (as browsers do)
+    let comments = html_comments(sequence);
+    for tag in RE_HTML_META.find_iter(sequence) {
+        if comments
+            .iter()
+            .any(|comment| comment.contains(&tag.start()))
+        {
+            continue;
+        }
+        if let Some(captures) = RE_HTML_META_CHARSET.captures(tag.as_bytes()) {
+            let syntax = match tag
+                .as_bytes()
+                .to_ascii_lowercase()
+                .windows(10)
+                .any(|w| w == b"http-equiv")
+            {
+                true => DeclarationSyntax::HtmlHttpEquiv,
+                false => DeclarationSyntax::HtmlMetaCharset,
+            };
+            let label = captures.get(1).map(|m| m.as_bytes()).unwrap_or_default();
+            found(syntax, tag.range(), label);
+        }
+    }
+
+    for (syntax, regex) in [
+        (DeclarationSyntax::MimeContentType, &*RE_MIME_CONTENT_TYPE),
+        (DeclarationSyntax::SqlSetNames, &*RE_SQL_SET_NAMES),
+        (DeclarationSyntax::EmacsModeline, &*RE_EMACS_MODELINE),
+        (DeclarationSyntax::VimModeline, &*RE_VIM_MODELINE),
+        (DeclarationSyntax::Pep263, &*RE_PEP263),
+    ] {
+        for_each_label(regex, sequence, |span, label| found(syntax, span, label));
+    }
+
+    // any later statement may set names again, so only the single one is trusted
+    let is_sql = |d: &Declaration| d.syntax == DeclarationSyntax::SqlSetNames;
+    if declarations.iter().filter(|d| is_sql(d)).count() > 1 {
+        for declaration in declarations.iter_mut().filter(|d| is_sql(d)) {
+            declaration.unambiguous = false;
+        }
+    }
+
+    declarations.sort_by_key(|d| d.span.start);
+    declarations
+}
+
+// Spans of HTML comments (unterminated one lasts till the end of sequence)
+fn html_comments(sequence: &[u8]) -> Vec<Range<usize>> {
+    let mut comments: Vec<Range<usize>> = vec![];
+    let mut from = 0;
+    while let Some(start) = find_subsequence(sequence, from, b"<!--") {
+        let end =
+            find_subsequence(sequence, start + 4, b"-->").map_or(sequence.len(), |end| end + 3);
+        comments.push(start..end);
+        from = end;
+    }
+    comments
+}
+
+// Call f with span and label (the first group) of every match
+fn for_each_label(regex: &Regex, sequence: &[u8], mut f: impl FnMut(Range<usize>, &[u8])) {
+    for captures in regex.captures_iter(sequence) {
+        if let (Some(whole), Some(label)) = (captures.get(0), captures.get(1)) {
+            f(whole.range(), label.as_bytes());
+        }
+    }
+}
+
+// IANA name of declared label, declarations use some non-WHATWG labels (f.e. "utf8mb4", "koi8_r")
+fn declared_encoding(label: &str) -> Option<&'static str> {
+    let label = label.trim().to_lowercase();
+    if let Some(&encoding) = DECLARATION_ALIASES.get(label.as_str()) {
+        return Some(encoding);
+    }
+    [label.clone(), label.replace('_', "-")]
+        .iter()
+        .find_map(|label| iana_name(label))
+        .and_then(|encoding| {
+            supported_encodings()
+                .into_iter()
+                .find(|&enc| enc == encoding)
+        })
+}
+
+// Check if declaration is placed where its syntax expects it
+fn is_unambiguous(sequence: &[u8], syntax: DeclarationSyntax, span: &Range<usize>) -> bool {
+    let before = &sequence[..span.start];
+    let line = before.iter().filter(|&&b| b == b'\n').count();
+    match syntax {
+        // only UTF-8 BOM may precede them
+        DeclarationSyntax::XmlDeclaration | DeclarationSyntax::CssCharset => {
+            before.is_empty() || before == b"\xef\xbb\xbf"
+        }
+        // browsers pre-scan the first 1024 bytes for meta tags
+        DeclarationSyntax::HtmlMetaCharset | DeclarationSyntax::HtmlHttpEquiv => span.start < 1024,
+        // the first line or the second one (after shebang)
+        DeclarationSyntax::Pep263 | DeclarationSyntax::EmacsModeline => line < 2,
+        // modelines are looked for in the first 5 lines
+        DeclarationSyntax::VimModeline => line < 5,
+        // top-level header (before the first empty line)
+        DeclarationSyntax::MimeContentType => {
+            !before.windows(2).any(|w| w == b"\n\n") && !before.windows(4).any(|w| w == b"\r\n\r\n")
+        }
+        // unless there are several of them (see find_declarations)
+        DeclarationSyntax::SqlSetNames => true,
+    }
+}
+
+// Encoding which is unambiguously declared, if all such declarations agree
+pub(crate) fn unambiguous_encoding(declarations: &[Declaration]) -> Option<&'static str> {
+    let mut declared = declarations
+        .iter()
+        .filter(|d| d.unambiguous)
+        .filter_map(|d| d.encoding);
+    let first = declared.next()?;
+    declared.all(|enc| enc == first).then_some(first)
+}
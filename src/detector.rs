@@ -0,0 +1,224 @@
+use crate::cd::{encoding_languages, mb_encoding_languages};
+use crate::codecs::{similar_encodings, supported_encodings};
+use crate::entity::{CharsetMatches, Language, NormalizerSettings};
+use crate::error::Error;
+use crate::report::DetectionReport;
+use crate::utils::{iana_name, is_multi_byte_encoding, ResolvedCodec};
+use crate::{detect_with, read_and_detect};
+use ahash::HashMap;
+use log::trace;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Detector
+// Settings validated and prepared once, to be reused over many detections
+/////////////////////////////////////////////////////////////////////////////////////
+
+/// Reusable charset detector.
+///
+/// Settings are validated and everything which does not depend on the payload
+/// (normalized include/exclude lists, order of encodings to probe, their codecs and similar
+/// code pages, priors of hinted encodings, languages targeted by each encoding) is computed
+/// once in `Detector::new`. Detector is `Send + Sync`, so a single instance can be shared
+/// between threads. Codecs registered after the detector is created are not probed by it.
+///
+/// ```rust
+/// use charset_normalizer_rs::detector::Detector;
+/// use charset_normalizer_rs::entity::NormalizerSettings;
+///
+/// let detector = Detector::new(NormalizerSettings::default()).unwrap();
+/// let result = detector.detect("Qu'est ce que une étoile?".as_bytes()).unwrap();
+/// assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Detector {
+    settings: NormalizerSettings,
+    encodings: Vec<&'static str>,
+    codecs: HashMap<&'static str, ResolvedCodec<'static>>,
+    similar_encodings: HashMap<&'static str, Vec<&'static str>>,
+    priors: Vec<(&'static str, f32)>,
+    target_languages: HashMap<&'static str, Vec<&'static Language>>,
+}
+
+impl Detector {
+    // Validate settings and prepare detector
+    pub fn new(settings: NormalizerSettings) -> Result<Self, Error> {
+        let mut settings = settings;
+        settings.validate()?;
+
+        // recheck include_encodings and exclude_encodings settings
+        settings.include_encodings = normalize_encodings(&settings.include_encodings)?;
+        if !settings.include_encodings.is_empty() {
+            trace!(
+                "include_encodings is set. Use this flag for debugging purpose. \
+            Limited list of encoding allowed : {}.",
+                settings.include_encodings.join(", ")
+            );
+        }
+        settings.exclude_encodings = normalize_encodings(&settings.exclude_encodings)?;
+        if !settings.exclude_encodings.is_empty() {
+            trace!(
+                "exclude_encodings is set. Use this flag for debugging purpose. \
+            Limited list of encoding allowed : {}.",
+                settings.exclude_encodings.join(", ")
+            );
+        }
+
+        // encodings which are allowed to be probed, in default order
+        let encodings: Vec<&'static str> = supported_encodings()
+            .into_iter()
+            .filter(|&enc| {
+                (settings.include_encodings.is_empty()
+                    || settings.include_encodings.iter().any(|e| e == enc))
+                    && !settings.exclude_encodings.iter().any(|e| e == enc)
+            })
+            .collect();
+
+        // resolve codecs & similar code pages, so that detection never looks them up
+        // (encodings without codec, f.e. "hz" which has no WHATWG label, fail probing)
+        let codecs = encodings
+            .iter()
+            .filter_map(|&enc| ResolvedCodec::resolve(enc).ok().map(|codec| (enc, codec)))
+            .collect();
+        let similar_encodings = encodings
+            .iter()
+            .map(|&enc| (enc, similar_encodings(enc)))
+            .filter(|(_, similar)| !similar.is_empty())
+            .collect();
+
+        // resolve hints into priors of allowed encodings
+        let priors: Vec<(&'static str, f32)> = settings
+            .hints
+            .resolve()
+            .into_iter()
+            .filter_map(|(hinted, weight)| {
+                encodings
+                    .iter()
+                    .find(|&&enc| enc == hinted)
+                    .map(|&enc| (enc, weight))
+            })
+            .collect();
+        if !priors.is_empty() {
+            trace!("Encodings hinted by caller: {:?}.", priors);
+        }
+
+        // detect target languages
+        let target_languages = encodings
+            .iter()
+            .map(|&enc| {
+                let languages = if is_multi_byte_encoding(enc) {
+                    mb_encoding_languages(enc)
+                } else {
+                    encoding_languages(enc.to_string())
+                };
+                (enc, languages)
+            })
+            .collect();
+
+        Ok(Detector {
+            settings,
+            encodings,
+            codecs,
+            similar_encodings,
+            priors,
+            target_languages,
+        })
+    }
+
+    // Choose decoding backend of WHATWG encodings for this detector only: encoding_rs (default)
+    // or `encoding` crate. Both give the same results, it is used to compare their speed.
+    #[cfg(feature = "encoding_rs")]
+    #[doc(hidden)]
+    pub fn with_encoding_rs(mut self, enabled: bool) -> Self {
+        for codec in self.codecs.values_mut() {
+            *codec = codec.with_encoding_rs(enabled);
+        }
+        self
+    }
+
+    // Settings with normalized encoding names
+    pub fn settings(&self) -> &NormalizerSettings {
+        &self.settings
+    }
+
+    // Encodings allowed to be probed, in default order
+    pub(crate) fn encodings(&self) -> &[&'static str] {
+        &self.encodings
+    }
+
+    // Codec of encoding allowed to be probed
+    pub(crate) fn codec(&self, encoding: &str) -> Result<ResolvedCodec<'static>, Error> {
+        self.codecs
+            .get(encoding)
+            .copied()
+            .ok_or_else(|| Error::UnknownEncoding(encoding.to_string()))
+    }
+
+    // Whether two single-byte encodings decode most of bytes the same way
+    pub(crate) fn is_cp_similar(&self, encoding_a: &str, encoding_b: &str) -> bool {
+        self.similar_encodings
+            .get(encoding_a)
+            .is_some_and(|similar| similar.contains(&encoding_b))
+    }
+
+    // Hinted encodings with their weights, the most weighted first
+    pub(crate) fn priors(&self) -> &[(&'static str, f32)] {
+        &self.priors
+    }
+
+    // Weight of encoding hinted by caller (0.0 if encoding was not hinted)
+    pub(crate) fn prior(&self, encoding: &str) -> f32 {
+        self.priors
+            .iter()
+            .find(|(enc, _)| *enc == encoding)
+            .map(|(_, weight)| *weight)
+            .unwrap_or_default()
+    }
+
+    // Languages the encoding is linked to
+    pub(crate) fn target_languages(&self, encoding: &str) -> Vec<&'static Language> {
+        self.target_languages
+            .get(encoding)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Same thing than the function `from_bytes`, but with prepared settings
+    pub fn detect(&self, bytes: &[u8]) -> Result<CharsetMatches, Error> {
+        detect_with(self, bytes, false).map(|(results, _)| results)
+    }
+
+    /// Same thing than the function `from_bytes_with_report`, but with prepared settings
+    pub fn detect_with_report(
+        &self,
+        bytes: &[u8],
+    ) -> Result<(CharsetMatches, DetectionReport), Error> {
+        detect_with(self, bytes, false)
+    }
+
+    /// Same thing than the function `from_path`, but with prepared settings
+    pub fn detect_path(&self, path: &Path) -> Result<CharsetMatches, Error> {
+        let file = File::open(path)?;
+        let file_size = file.metadata().map(|m| m.len()).unwrap_or_default();
+        read_and_detect(self, file, file_size as usize)
+    }
+
+    /// Same thing than the function `from_reader`, but with prepared settings
+    pub fn detect_reader<R: Read>(&self, reader: R) -> Result<CharsetMatches, Error> {
+        read_and_detect(self, reader, 0)
+    }
+}
+
+// Replace every encoding label by its IANA name
+fn normalize_encodings(encodings: &[String]) -> Result<Vec<String>, Error> {
+    encodings
+        .iter()
+        .map(|enc| {
+            iana_name(enc)
+                .map(String::from)
+                .ok_or_else(|| Error::UnknownEncoding(enc.clone()))
+        })
+        .collect()
+}
@@ -1,16 +1,33 @@
 #![allow(unused_variables)]
 
 use crate::cd::{encoding_languages, mb_encoding_languages};
-use crate::consts::{IANA_SUPPORTED_ALIASES, TOO_BIG_SEQUENCE};
-use crate::utils::{decode, iana_name, is_multi_byte_encoding, range_scan};
+use crate::codecs::registered_aliases;
+use crate::consts::{
+    CONTENT_TYPE_PRIOR_WEIGHT, IANA_SUPPORTED_ALIASES, LOCALE_PRIOR_WEIGHT, TLD_PRIOR_WEIGHT,
+    TOO_BIG_SEQUENCE,
+};
+use crate::error::Error;
+use crate::md::builtin_plugins;
+use crate::md::plugins::MessDetectorPlugin;
+use crate::utils::{
+    content_type_charset, decode, iana_name, is_multi_byte_encoding, locale_encodings, range_scan,
+    tld_encodings, ResolvedCodec,
+};
 use encoding::DecoderTrap;
+use once_cell::sync::OnceCell;
 use ordered_float::OrderedFloat;
-use std::borrow::Cow;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
 use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
 use std::ops::Index;
+use std::panic::RefUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Arc;
 
 /////////////////////////////////////////////////////////////////////////////////////
 // Languages
@@ -65,6 +82,14 @@ impl Display for Language {
     }
 }
 
+// Language is serialized as its name
+#[cfg(feature = "serde")]
+impl Serialize for Language {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////////
 // CoherenceMatch & CoherenceMatches
 /////////////////////////////////////////////////////////////////////////////////////
@@ -83,16 +108,25 @@ pub(crate) type CoherenceMatches = Vec<CoherenceMatch>;
 
 #[derive(Clone)]
 pub struct CharsetMatch {
-    payload: Cow<'static, [u8]>,
+    // shared between all matches of the same input, never copied
+    payload: Arc<[u8]>,
     encoding: String,
 
     mean_mess_ratio: OrderedFloat<f32>,
+    // mean ratio of every mess detector (empty if chaos was not measured)
+    mess_breakdown: Vec<(String, f32)>,
     coherence_matches: CoherenceMatches,
 
     has_sig_or_bom: bool,
+    // payload is only a prefix of the input (read budget was exhausted)
+    is_truncated: bool,
+    // weight of encoding hinted by caller (0.0 if there were no hints)
+    prior: f32,
 
     submatch: Vec<CharsetMatch>,
-    decoded_payload: Option<String>,
+    // decoded lazily on first access, with codec resolved by detector (if any)
+    codec: Option<ResolvedCodec<'static>>,
+    decoded_payload: OnceCell<Option<String>>,
 }
 
 impl Display for CharsetMatch {
@@ -110,20 +144,24 @@ impl Debug for CharsetMatch {
 impl Default for CharsetMatch {
     fn default() -> Self {
         CharsetMatch {
-            payload: Cow::Borrowed(&[]),
+            payload: Arc::from(&[][..]),
             encoding: "utf-8".to_string(),
             mean_mess_ratio: OrderedFloat(0.0),
+            mess_breakdown: vec![],
             coherence_matches: vec![],
             has_sig_or_bom: false,
+            is_truncated: false,
+            prior: 0.0,
             submatch: vec![],
-            decoded_payload: None,
+            codec: None,
+            decoded_payload: OnceCell::new(),
         }
     }
 }
 
 impl PartialEq<Self> for CharsetMatch {
     fn eq(&self, other: &Self) -> bool {
-        self.encoding == other.encoding && self.decoded_payload == other.decoded_payload
+        self.encoding == other.encoding && self.decoded_payload() == other.decoded_payload()
     }
 }
 
@@ -132,8 +170,9 @@ impl Eq for CharsetMatch {}
 impl Ord for CharsetMatch {
     fn cmp(&self, other: &Self) -> Ordering {
         let mess_difference = (self.mean_mess_ratio - other.mean_mess_ratio).abs();
-        let coherence_a = OrderedFloat(self.coherence());
-        let coherence_b = OrderedFloat(other.coherence());
+        // caller-supplied prior strengthens coherence of hinted encoding
+        let coherence_a = OrderedFloat(self.coherence() + self.prior);
+        let coherence_b = OrderedFloat(other.coherence() + other.prior);
         let coherence_difference = (coherence_a - coherence_b).abs();
 
         // Below 1% difference --> Use Coherence
@@ -161,28 +200,57 @@ impl PartialOrd<Self> for CharsetMatch {
 impl CharsetMatch {
     // Init function
     pub(crate) fn new(
-        payload: Cow<'static, [u8]>,
+        payload: Arc<[u8]>,
         encoding: &str,
         mean_mess_ratio: f32,
         has_sig_or_bom: bool,
         coherence_matches: &CoherenceMatches,
-        decoded_payload: Option<&str>,
+        decoded_payload: Option<String>,
     ) -> Self {
         CharsetMatch {
-            payload: payload.clone(),
+            payload,
             encoding: String::from(encoding),
             mean_mess_ratio: OrderedFloat(mean_mess_ratio),
+            mess_breakdown: vec![],
             coherence_matches: coherence_matches.clone(),
             has_sig_or_bom,
+            is_truncated: false,
+            prior: 0.0,
             submatch: vec![],
-            decoded_payload: decoded_payload.map(String::from).or_else(|| {
-                decode(&payload, encoding, DecoderTrap::Strict, false, true)
-                    .ok()
-                    .map(|res| res.strip_prefix('\u{feff}').unwrap_or(&res).to_string())
-            }),
+            codec: None,
+            decoded_payload: match decoded_payload {
+                Some(payload) => OnceCell::with_value(Some(payload)),
+                None => OnceCell::new(),
+            },
         }
     }
 
+    // Set weight of encoding hinted by caller
+    pub(crate) fn with_prior(mut self, prior: f32) -> Self {
+        self.prior = prior;
+        self
+    }
+
+    // Set codec used to decode payload lazily
+    pub(crate) fn with_codec(mut self, codec: ResolvedCodec<'static>) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
+    // Set mean ratios of mess detectors
+    pub(crate) fn with_mess_breakdown(mut self, mess_breakdown: Vec<(String, f32)>) -> Self {
+        self.mess_breakdown = mess_breakdown;
+        self
+    }
+
+    // Mark match (and its submatches) as made over a prefix of the input
+    pub(crate) fn set_truncated(&mut self) {
+        self.is_truncated = true;
+        self.submatch
+            .iter_mut()
+            .for_each(CharsetMatch::set_truncated);
+    }
+
     // Add submatch
     pub(crate) fn add_submatch(&mut self, submatch: &CharsetMatch) {
         self.submatch.push(submatch.clone());
@@ -190,11 +258,13 @@ impl CharsetMatch {
     }
 
     // Get encoding aliases according to https://encoding.spec.whatwg.org/encodings.json
+    // (or ones of registered codec)
     pub fn encoding_aliases(&self) -> Vec<&'static str> {
         IANA_SUPPORTED_ALIASES
             .get(self.encoding.as_str())
             .cloned()
-            .expect("Problem with static HashMap IANA_SUPPORTED_ALIASES")
+            .or_else(|| registered_aliases(&self.encoding))
+            .unwrap_or_default()
     }
     // byte_order_mark
     pub fn bom(&self) -> bool {
@@ -206,6 +276,25 @@ impl CharsetMatch {
     pub fn chaos(&self) -> f32 {
         self.mean_mess_ratio.0
     }
+    // Name and ratio of every mess detector, averaged across sampled chunks (they sum up to chaos).
+    // Empty if chaos was not measured (f.e. for fallback match).
+    pub fn mess_breakdown(&self) -> &[(String, f32)] {
+        &self.mess_breakdown
+    }
+    // Flavour of UTF-8 (None if encoding is not one of UTF-8 variants)
+    pub fn utf8_variant(&self) -> Option<Utf8Variant> {
+        match self.encoding.as_str() {
+            "utf-8" => Some(Utf8Variant::Standard),
+            "cesu-8" => Some(Utf8Variant::Cesu8),
+            "mutf-8" => Some(Utf8Variant::Modified),
+            "wtf-8" => Some(Utf8Variant::Wtf8),
+            _ => None,
+        }
+    }
+    // Weight of encoding given by caller's hints (0.0 if encoding was not hinted)
+    pub fn prior(&self) -> f32 {
+        self.prior
+    }
     // Most probable language found in decoded sequence. If none were detected or inferred, the property will return
     // Language::Unknown
     pub fn most_probably_language(&self) -> &'static Language {
@@ -253,6 +342,11 @@ impl CharsetMatch {
     pub fn raw(&self) -> &[u8] {
         &self.payload
     }
+    // Whether only a prefix of the input was read (`max_read_bytes` budget was exhausted), so
+    // raw bytes and decoded payload do not cover all of it
+    pub fn is_truncated(&self) -> bool {
+        self.is_truncated
+    }
     // Return chaos in percents with rounding
     pub fn chaos_percents(&self) -> f32 {
         self.chaos() * 100.0
@@ -261,6 +355,10 @@ impl CharsetMatch {
     pub fn coherence_percents(&self) -> f32 {
         self.coherence() * 100.0
     }
+    // Languages with their coherence
+    pub(crate) fn coherence_matches(&self) -> &CoherenceMatches {
+        &self.coherence_matches
+    }
     // Most relevant language coherence
     pub fn coherence(&self) -> f32 {
         self.coherence_matches
@@ -269,9 +367,24 @@ impl CharsetMatch {
             .unwrap_or_default()
     }
 
-    // To recalc decoded_payload field
+    // Decoded payload, it is computed once on first access
     pub fn decoded_payload(&self) -> Option<&str> {
-        self.decoded_payload.as_deref()
+        self.decoded_payload
+            .get_or_init(|| {
+                match self.codec {
+                    Some(codec) => codec.decode(&self.payload, DecoderTrap::Strict, false, true),
+                    None => decode(
+                        &self.payload,
+                        &self.encoding,
+                        DecoderTrap::Strict,
+                        false,
+                        true,
+                    ),
+                }
+                .ok()
+                .map(|res| res.strip_prefix('\u{feff}').unwrap_or(&res).to_string())
+            })
+            .as_deref()
     }
 
     // The complete list of encodings that output the exact SAME str result and therefore could be the originating
@@ -292,6 +405,59 @@ impl CharsetMatch {
     }
 }
 
+// CharsetMatch is serialized as object (the same one, which CLI outputs), chaos and coherence
+// are given in percents, formatted with 1 decimal place:
+// {
+//     "encoding": "big5",
+//     "encoding_aliases": ["big5_tw", "csbig5", "x_mac_trad_chinese"],
+//     "alternative_encodings": ["big5hkscs", "cp950"],
+//     "language": "Chinese",
+//     "alphabets": ["Basic Latin", "CJK Unified Ideographs"],
+//     "has_sig_or_bom": false,
+//     "chaos": "0.0",
+//     "coherence": "12.2"
+// }
+// Single match does not know if it is the preferred one, so "is_preferred" key is added
+// only by serialization of CharsetMatches.
+#[cfg(feature = "serde")]
+impl Serialize for CharsetMatch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serialize_fields(None, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl CharsetMatch {
+    // Serialize match, with "is_preferred" key if it is given
+    fn serialize_fields<S: Serializer>(
+        &self,
+        is_preferred: Option<bool>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let alternative_encodings: Vec<String> = self
+            .suitable_encodings()
+            .into_iter()
+            .filter(|e| e != self.encoding())
+            .collect();
+
+        let len = 9 + usize::from(is_preferred.is_some());
+        let mut state = serializer.serialize_struct("CharsetMatch", len)?;
+        state.serialize_field("encoding", self.encoding())?;
+        state.serialize_field("encoding_aliases", &self.encoding_aliases())?;
+        state.serialize_field("alternative_encodings", &alternative_encodings)?;
+        state.serialize_field("language", self.most_probably_language())?;
+        state.serialize_field("alphabets", &self.unicode_ranges())?;
+        state.serialize_field("has_sig_or_bom", &self.bom())?;
+        state.serialize_field("is_truncated", &self.is_truncated())?;
+        state.serialize_field("chaos", &format!("{:.1}", self.chaos_percents()))?;
+        state.serialize_field("coherence", &format!("{:.1}", self.coherence_percents()))?;
+        if let Some(is_preferred) = is_preferred {
+            state.serialize_field("is_preferred", &is_preferred)?;
+        }
+        state.end()
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////////
 // CharsetMatches
 // Container with every CharsetMatch items ordered by default from most probable
@@ -350,6 +516,10 @@ impl CharsetMatches {
             .iter()
             .find(|&i| i.suitable_encodings().contains(&encoding.to_string()))
     }
+    // Mark every match as made over a prefix of the input
+    pub(crate) fn set_truncated(&mut self) {
+        self.items.iter_mut().for_each(CharsetMatch::set_truncated);
+    }
     // Resort items by relevancy (for internal use)
     fn resort(items: &mut [CharsetMatch]) {
         items.sort_unstable();
@@ -375,6 +545,30 @@ impl CharsetMatches {
     }
 }
 
+// CharsetMatches is serialized as list of matches, from the most probable one, every match has
+// additional "is_preferred" key (true only for the first one)
+#[cfg(feature = "serde")]
+impl Serialize for CharsetMatches {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.iter()
+                .enumerate()
+                .map(|(index, m)| PreferredMatch(m, index == 0)),
+        )
+    }
+}
+
+// Match of CharsetMatches, which knows if it is the preferred (best) one
+#[cfg(feature = "serde")]
+struct PreferredMatch<'a>(&'a CharsetMatch, bool);
+
+#[cfg(feature = "serde")]
+impl Serialize for PreferredMatch<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_fields(Some(self.1), serializer)
+    }
+}
+
 impl Index<usize> for CharsetMatches {
     type Output = CharsetMatch;
     fn index(&self, index: usize) -> &Self::Output {
@@ -398,7 +592,338 @@ impl<'a> Iterator for CharsetMatchesIter<'a> {
     }
 }
 
+/// Way of picking `steps` chunks (each of `chunk_size` length) from the sequence
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SamplingStrategy {
+    /// Chunks at evenly spaced offsets
+    #[default]
+    EvenlySpaced,
+    /// Chunks centred on non-ASCII characters (bytes), spread over the sequence.
+    /// Useful for mostly ASCII content with a few non-ASCII islands.
+    NonAsciiDense,
+    /// One half of chunks from the beginning and another one from the end of the sequence
+    HeadTail,
+    /// Chunks at random offsets, reproducible with the same seed
+    Random { seed: u64 },
+}
+
+/// Flavour of UTF-8 which content was found in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Utf8Variant {
+    /// Standard UTF-8
+    Standard,
+    /// CESU-8: supplementary characters are written as two 3-bytes surrogates (Oracle)
+    Cesu8,
+    /// Modified UTF-8: CESU-8 with NUL written as "C0 80" (Java `DataOutputStream`, JNI)
+    Modified,
+    /// WTF-8: UTF-8 with lone surrogates (Windows file names), decoded as U+FFFD
+    Wtf8,
+}
+
+/// Kind of content, which defines how chunks for mess & coherence probing are prepared
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ContentMode {
+    /// Content is probed as is
+    #[default]
+    PlainText,
+    /// HTML / XML tags (with attributes), comments, scripts and styles are stripped,
+    /// and character references are resolved before chunking.
+    /// Matches still decode the whole untouched content.
+    Markup,
+    /// Markup mode is used if content looks like HTML / XML document
+    Auto,
+}
+
+/// Context known by caller, which makes some encodings more likely.
+/// Hinted encodings are probed earlier, and their weights (priors) are added to
+/// coherence when matches with close chaos are ranked.
+///
+/// ```rust
+/// use charset_normalizer_rs::entity::EncodingHints;
+///
+/// let hints = EncodingHints::default()
+///     .content_type("text/plain; charset=KOI8-R")
+///     .locale("ru_RU")
+///     .tld(".ru");
+/// assert_eq!(hints.priors, vec![(String::from("koi8-r"), 1.0)]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct EncodingHints {
+    /// Encodings with their weights (0.0 <= weight <= 1.0)
+    pub priors: Vec<(String, f32)>,
+    /// Locale of the user or the content author, f.e. `ru_RU` or `ru_RU.KOI8-R`
+    pub locale: Option<String>,
+    /// Top-level domain of the content source, f.e. `jp` or `example.co.jp`
+    pub tld: Option<String>,
+}
+
+impl EncodingHints {
+    // Add encoding prior with its weight
+    pub fn prior(mut self, encoding: impl Into<String>, weight: f32) -> Self {
+        self.priors.push((encoding.into(), weight));
+        self
+    }
+    // Add charset of HTTP Content-Type header (if it is given and known) as a strong prior
+    pub fn content_type(self, content_type: &str) -> Self {
+        match content_type_charset(content_type) {
+            Some(charset) => self.prior(charset, CONTENT_TYPE_PRIOR_WEIGHT),
+            None => self,
+        }
+    }
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+    pub fn tld(mut self, tld: impl Into<String>) -> Self {
+        self.tld = Some(tld.into());
+        self
+    }
+
+    // Check that priors are known encodings with weights in range
+    pub fn validate(&self) -> Result<(), Error> {
+        for (enc, weight) in &self.priors {
+            if iana_name(enc).is_none() {
+                return Err(Error::UnknownEncoding(enc.clone()));
+            }
+            if !(0.0..=1.0).contains(weight) {
+                return Err(Error::InvalidSettings(format!(
+                    "weight of {} prior should be between 0.0 and 1.0",
+                    enc
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Hinted encodings (IANA names) with their weights, the most weighted first.
+    // Encoding hinted several times gets the greatest weight.
+    pub(crate) fn resolve(&self) -> Vec<(String, f32)> {
+        let hinted = self
+            .priors
+            .iter()
+            .filter_map(|(enc, weight)| iana_name(enc).map(|enc| (enc.to_string(), *weight)))
+            .chain(
+                self.locale
+                    .iter()
+                    .flat_map(|locale| locale_encodings(locale))
+                    .map(|enc| (enc, LOCALE_PRIOR_WEIGHT)),
+            )
+            .chain(
+                self.tld
+                    .iter()
+                    .flat_map(|tld| tld_encodings(tld))
+                    .map(|enc| (enc, TLD_PRIOR_WEIGHT)),
+            );
+
+        let mut resolved: Vec<(String, f32)> = vec![];
+        for (encoding, weight) in hinted {
+            match resolved.iter_mut().find(|(enc, _)| *enc == encoding) {
+                Some((_, w)) => *w = w.max(weight),
+                None => resolved.push((encoding, weight)),
+            }
+        }
+        resolved.sort_by(|a, b| b.1.total_cmp(&a.1));
+        resolved
+    }
+}
+
+// Source of identifiers of plugin factories
+static PLUGIN_FACTORY_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Factory of custom mess detection plugin (a new plugin is needed for every probed chunk).
+/// Plugins must give the same ratio for the same content, as mess ratios are cached.
+/// Factory is unwind safe, so settings can be used inside of `catch_unwind`.
 #[derive(Clone)]
+pub struct MessDetectorPluginFactory {
+    factory: Arc<dyn Fn() -> Box<dyn MessDetectorPlugin> + Send + Sync + RefUnwindSafe>,
+    // name of created plugins, and identifier shared by clones only
+    name: String,
+    id: u64,
+}
+
+impl MessDetectorPluginFactory {
+    // A plugin is created once, to know its name
+    pub fn new<F>(factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn MessDetectorPlugin> + Send + Sync + RefUnwindSafe + 'static,
+    {
+        let name = factory().name().to_string();
+        MessDetectorPluginFactory {
+            factory: Arc::new(factory),
+            name,
+            id: PLUGIN_FACTORY_ID.fetch_add(1, AtomicOrdering::Relaxed),
+        }
+    }
+    // Create a new plugin
+    pub fn create(&self) -> Box<dyn MessDetectorPlugin> {
+        (self.factory)()
+    }
+    // Name of created plugins
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    // Identifier of factory, clones of the same factory share it
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Debug for MessDetectorPluginFactory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "MessDetectorPluginFactory({})", self.name)
+    }
+}
+
+// Factories are equal only if they are clones of each other
+impl PartialEq for MessDetectorPluginFactory {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+// Identifiers of extra plugin factories, disabled plugins and weights of mess detection settings
+pub(crate) type MessDetectorCacheKey = (Vec<u64>, Vec<String>, Vec<(String, OrderedFloat<f32>)>);
+
+/// Mess detection plugins used for probing: built-in ones (except disabled ones) and extra ones.
+/// Ratio of every plugin is multiplied by its weight (1.0 by default).
+/// Extra plugins are not (de)serialized.
+///
+/// ```rust
+/// use charset_normalizer_rs::entity::MessDetectorSettings;
+///
+/// let settings = MessDetectorSettings::default()
+///     .disable("ArchaicUpperLowerPlugin")
+///     .weight("SuperWeirdWordPlugin", 0.5);
+/// assert!(settings.validate().is_ok());
+/// assert!(MessDetectorSettings::default().disable("NoSuchPlugin").validate().is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MessDetectorSettings {
+    /// Extra plugins (their names must differ from names of built-in and other extra plugins)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub plugins: Vec<MessDetectorPluginFactory>,
+    /// Names of disabled built-in plugins
+    pub disabled: Vec<String>,
+    /// Plugin names with their weights (weight >= 0.0)
+    pub weights: Vec<(String, f32)>,
+}
+
+impl MessDetectorSettings {
+    // Add extra plugin
+    pub fn plugin<F>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn MessDetectorPlugin> + Send + Sync + RefUnwindSafe + 'static,
+    {
+        self.plugins.push(MessDetectorPluginFactory::new(factory));
+        self
+    }
+    // Disable built-in plugin
+    pub fn disable(mut self, name: impl Into<String>) -> Self {
+        self.disabled.push(name.into());
+        self
+    }
+    // Set weight of plugin (built-in or extra one)
+    pub fn weight(mut self, name: impl Into<String>, weight: f32) -> Self {
+        let name = name.into();
+        self.weights.retain(|(n, _)| *n != name);
+        self.weights.push((name, weight));
+        self
+    }
+
+    // Check that disabled & weighted plugins exist, extra plugins have names of their own
+    // and weights are not negative
+    pub fn validate(&self) -> Result<(), Error> {
+        let builtin_names: Vec<String> = builtin_plugins()
+            .iter()
+            .map(|d| d.name().to_string())
+            .collect();
+        for (idx, factory) in self.plugins.iter().enumerate() {
+            if builtin_names.iter().any(|name| name == factory.name())
+                || self.plugins[..idx]
+                    .iter()
+                    .any(|other| other.name() == factory.name())
+            {
+                return Err(Error::InvalidSettings(format!(
+                    "{} mess detection plugin is already used",
+                    factory.name()
+                )));
+            }
+        }
+        for name in &self.disabled {
+            if !builtin_names.contains(name) {
+                return Err(Error::InvalidSettings(format!(
+                    "{} is not a built-in mess detection plugin",
+                    name
+                )));
+            }
+        }
+        for (name, weight) in &self.weights {
+            if !builtin_names.contains(name)
+                && !self.plugins.iter().any(|factory| factory.name() == name)
+            {
+                return Err(Error::InvalidSettings(format!(
+                    "{} is not a mess detection plugin",
+                    name
+                )));
+            }
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(Error::InvalidSettings(format!(
+                    "weight of {} plugin should be 0.0 or greater",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Only built-in plugins with default weights are used
+    pub(crate) fn is_default(&self) -> bool {
+        self.plugins.is_empty() && self.disabled.is_empty() && self.weights.is_empty()
+    }
+
+    // Settings which give the same ratios have the same key
+    pub(crate) fn cache_key(&self) -> MessDetectorCacheKey {
+        (
+            self.plugins.iter().map(|factory| factory.id()).collect(),
+            self.disabled.clone(),
+            self.weights
+                .iter()
+                .map(|(name, weight)| (name.clone(), OrderedFloat(*weight)))
+                .collect(),
+        )
+    }
+
+    // New plugins with their weights
+    pub(crate) fn detectors(&self) -> Vec<(Box<dyn MessDetectorPlugin>, f32)> {
+        builtin_plugins()
+            .into_iter()
+            .filter(|d| !self.disabled.iter().any(|name| name == d.name()))
+            .chain(self.plugins.iter().map(|factory| factory.create()))
+            .map(|d| {
+                let weight = self
+                    .weights
+                    .iter()
+                    .find(|(name, _)| name == d.name())
+                    .map_or(1.0, |(_, weight)| *weight);
+                (d, weight)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct NormalizerSettings {
     /// How many steps (chunks) should be used from file
     pub steps: usize,
@@ -416,6 +941,17 @@ pub struct NormalizerSettings {
     pub language_threshold: OrderedFloat<f32>,
     /// Allow fallback to ASCII / UTF-8
     pub enable_fallback: bool,
+    /// Maximum amount of bytes to read from reader or file (None, the default, means read
+    /// everything). Matches made over a part of the input are marked by `CharsetMatch::is_truncated`.
+    pub max_read_bytes: Option<usize>,
+    /// How chunks for mess & coherence probing are picked from the sequence
+    pub sampling_strategy: SamplingStrategy,
+    /// Encodings hinted by caller's context (transport headers, locale, TLD)
+    pub hints: EncodingHints,
+    /// How content is prepared for mess & coherence probing
+    pub content_mode: ContentMode,
+    /// Plugins used for mess detection
+    pub mess_detector: MessDetectorSettings,
 }
 
 impl Default for NormalizerSettings {
@@ -429,6 +965,177 @@ impl Default for NormalizerSettings {
             preemptive_behaviour: true,
             language_threshold: OrderedFloat(0.1),
             enable_fallback: true,
+            max_read_bytes: None,
+            sampling_strategy: SamplingStrategy::default(),
+            hints: EncodingHints::default(),
+            content_mode: ContentMode::default(),
+            mess_detector: MessDetectorSettings::default(),
         }
     }
 }
+
+impl NormalizerSettings {
+    // Builder of settings, which are validated on build
+    pub fn builder() -> NormalizerSettingsBuilder {
+        NormalizerSettingsBuilder::default()
+    }
+
+    // Check that settings are usable for detection
+    pub fn validate(&self) -> Result<(), Error> {
+        check_sampling(self.steps, self.chunk_size)?;
+        check_ratio("threshold", self.threshold.0)?;
+        check_ratio("language_threshold", self.language_threshold.0)?;
+        check_max_read_bytes(self.max_read_bytes)?;
+        check_encodings(&self.include_encodings)?;
+        check_encodings(&self.exclude_encodings)?;
+        self.hints.validate()?;
+        self.mess_detector.validate()
+    }
+}
+
+// Sampling takes `steps` chunks of `chunk_size` units, so both have to be positive
+// and the whole sample has to be addressable
+fn check_sampling(steps: usize, chunk_size: usize) -> Result<(), Error> {
+    if steps == 0 {
+        return invalid_settings("steps should be greater than 0");
+    }
+    if chunk_size == 0 {
+        return invalid_settings("chunk_size should be greater than 0");
+    }
+    if chunk_size.checked_mul(steps).is_none() {
+        return invalid_settings("chunk_size * steps should not overflow usize");
+    }
+    Ok(())
+}
+
+fn check_ratio(name: &str, value: f32) -> Result<(), Error> {
+    match (0.0..=1.0).contains(&value) {
+        true => Ok(()),
+        false => invalid_settings(&format!("{} should be between 0.0 and 1.0", name)),
+    }
+}
+
+fn check_max_read_bytes(max_read_bytes: Option<usize>) -> Result<(), Error> {
+    match max_read_bytes {
+        Some(0) => invalid_settings("max_read_bytes should be greater than 0"),
+        _ => Ok(()),
+    }
+}
+
+fn check_encodings(encodings: &[String]) -> Result<(), Error> {
+    match encodings.iter().find(|enc| iana_name(enc).is_none()) {
+        Some(enc) => Err(Error::UnknownEncoding(enc.clone())),
+        None => Ok(()),
+    }
+}
+
+fn invalid_settings(message: &str) -> Result<(), Error> {
+    Err(Error::InvalidSettings(String::from(message)))
+}
+
+/////////////////////////////////////////////////////////////////////////////////////
+// NormalizerSettingsBuilder
+/////////////////////////////////////////////////////////////////////////////////////
+
+/// Builder of `NormalizerSettings`. Setters of values which may be invalid check them right away
+/// and return `Error` (as `NormalizerSettings::validate` does), so invalid settings can't be built.
+///
+/// ```rust
+/// use charset_normalizer_rs::entity::NormalizerSettings;
+/// use charset_normalizer_rs::error::Error;
+///
+/// let settings = NormalizerSettings::builder()
+///     .threshold(0.1)?
+///     .exclude_encodings(["koi8-u"])?
+///     .preemptive_behaviour(false)
+///     .build()?;
+/// assert_eq!(settings.threshold.0, 0.1);
+///
+/// assert!(NormalizerSettings::builder().steps(0).is_err());
+/// assert!(NormalizerSettings::builder().chunk_size(2)?.steps(usize::MAX).is_err());
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NormalizerSettingsBuilder {
+    settings: NormalizerSettings,
+}
+
+impl NormalizerSettingsBuilder {
+    pub fn steps(mut self, steps: usize) -> Result<Self, Error> {
+        check_sampling(steps, self.settings.chunk_size)?;
+        self.settings.steps = steps;
+        Ok(self)
+    }
+    pub fn chunk_size(mut self, chunk_size: usize) -> Result<Self, Error> {
+        check_sampling(self.settings.steps, chunk_size)?;
+        self.settings.chunk_size = chunk_size;
+        Ok(self)
+    }
+    pub fn threshold(mut self, threshold: f32) -> Result<Self, Error> {
+        check_ratio("threshold", threshold)?;
+        self.settings.threshold = OrderedFloat(threshold);
+        Ok(self)
+    }
+    pub fn include_encodings<I, S>(mut self, encodings: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let encodings: Vec<String> = encodings.into_iter().map(Into::into).collect();
+        check_encodings(&encodings)?;
+        self.settings.include_encodings = encodings;
+        Ok(self)
+    }
+    pub fn exclude_encodings<I, S>(mut self, encodings: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let encodings: Vec<String> = encodings.into_iter().map(Into::into).collect();
+        check_encodings(&encodings)?;
+        self.settings.exclude_encodings = encodings;
+        Ok(self)
+    }
+    pub fn preemptive_behaviour(mut self, preemptive_behaviour: bool) -> Self {
+        self.settings.preemptive_behaviour = preemptive_behaviour;
+        self
+    }
+    pub fn language_threshold(mut self, language_threshold: f32) -> Result<Self, Error> {
+        check_ratio("language_threshold", language_threshold)?;
+        self.settings.language_threshold = OrderedFloat(language_threshold);
+        Ok(self)
+    }
+    pub fn enable_fallback(mut self, enable_fallback: bool) -> Self {
+        self.settings.enable_fallback = enable_fallback;
+        self
+    }
+    pub fn max_read_bytes(mut self, max_read_bytes: Option<usize>) -> Result<Self, Error> {
+        check_max_read_bytes(max_read_bytes)?;
+        self.settings.max_read_bytes = max_read_bytes;
+        Ok(self)
+    }
+    pub fn sampling_strategy(mut self, sampling_strategy: SamplingStrategy) -> Self {
+        self.settings.sampling_strategy = sampling_strategy;
+        self
+    }
+    pub fn hints(mut self, hints: EncodingHints) -> Result<Self, Error> {
+        hints.validate()?;
+        self.settings.hints = hints;
+        Ok(self)
+    }
+    pub fn content_mode(mut self, content_mode: ContentMode) -> Self {
+        self.settings.content_mode = content_mode;
+        self
+    }
+    pub fn mess_detector(mut self, mess_detector: MessDetectorSettings) -> Result<Self, Error> {
+        mess_detector.validate()?;
+        self.settings.mess_detector = mess_detector;
+        Ok(self)
+    }
+
+    // Return settings (values are checked by setters, settings are checked once more as a whole)
+    pub fn build(self) -> Result<NormalizerSettings, Error> {
+        self.settings.validate()?;
+        Ok(self.settings)
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Error
+/////////////////////////////////////////////////////////////////////////////////////
+
+/// Errors returned by the library
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Given name is not a label of any supported encoding
+    UnknownEncoding(String),
+    /// Reading of file or stream has failed
+    Io(std::io::Error),
+    /// Bytes sequence does not fit encoding, `offset` points to the first problematic byte
+    Decode { encoding: String, offset: usize },
+    /// String contains characters which are not representable in encoding
+    Encode { encoding: String },
+    /// Detection settings are not valid
+    InvalidSettings(String),
+    /// Label of registered codec is already a label of supported encoding
+    DuplicateEncoding(String),
+    /// Label of registered codec is empty or not lowercase
+    InvalidLabel(String),
+    /// Detection has panicked (only reported by batch detection, which goes on with other files)
+    Panic(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownEncoding(name) => write!(f, "{name} is not a valid encoding name"),
+            Error::Io(err) => write!(f, "I/O error: {err}"),
+            Error::Decode { encoding, offset } => {
+                write!(f, "Cannot decode bytes with {encoding} at index {offset}")
+            }
+            Error::Encode { encoding } => write!(f, "Cannot encode string with {encoding}"),
+            Error::InvalidSettings(message) => write!(f, "Invalid settings: {message}"),
+            Error::DuplicateEncoding(label) => {
+                write!(f, "{label} is already a label of supported encoding")
+            }
+            Error::InvalidLabel(label) => {
+                write!(f, "{label} is not a valid label (it must be lowercase)")
+            }
+            Error::Panic(message) => write!(f, "Detection has panicked: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,241 @@
+use crate::codecs::supported_encodings;
+use crate::entity::CharsetMatches;
+use crate::utils::find_subsequence;
+use encoding::label::encoding_from_whatwg_label;
+
+/////////////////////////////////////////////////////////////////////////////////////
+// HTML encoding sniffing
+// https://html.spec.whatwg.org/multipage/parsing.html#determining-the-character-encoding
+/////////////////////////////////////////////////////////////////////////////////////
+
+/// Step of HTML encoding sniffing which decided the encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SniffingStep {
+    /// UTF-8, UTF-16LE or UTF-16BE byte order mark
+    ByteOrderMark,
+    /// Charset of transport layer (`Content-Type` header)
+    TransportLayer,
+    /// `<meta>` tag found by prescan of the first 1024 bytes
+    MetaPrescan,
+    /// Statistical detection (the same one as `from_bytes` does)
+    Detection,
+}
+
+/// Result of HTML encoding sniffing
+#[derive(Debug)]
+pub struct HtmlSniffing {
+    /// Step which decided the encoding
+    pub decided_by: SniffingStep,
+    /// Matches (the only one, unless encoding was decided by statistical detection)
+    pub matches: CharsetMatches,
+}
+
+// Encoding of byte order mark, only ones which browsers take into account
+pub(crate) fn bom_encoding(body: &[u8]) -> Option<&'static str> {
+    if body.starts_with(b"\xef\xbb\xbf") {
+        Some("utf-8")
+    } else if body.starts_with(b"\xfe\xff") {
+        Some("utf-16be")
+    } else if body.starts_with(b"\xff\xfe") {
+        Some("utf-16le")
+    } else {
+        None
+    }
+}
+
+// Encoding of charset parameter of Content-Type header, only WHATWG labels are taken into account
+pub(crate) fn transport_encoding(content_type: &str) -> Option<&'static str> {
+    let content_type = content_type.to_ascii_lowercase();
+    charset_from_content(content_type.as_bytes()).and_then(get_encoding)
+}
+
+/// Prescan the first 1024 bytes of HTML document for `<meta charset>` or
+/// `<meta http-equiv="Content-Type">` tag, as browsers do.
+///
+/// ```rust
+/// use charset_normalizer_rs::html::prescan;
+///
+/// assert_eq!(prescan(b"<!-- <meta charset=koi8-r> --><meta charset='cp1251'>"), Some("windows-1251"));
+/// assert_eq!(prescan(b"<meta content='text/html; charset=utf-16le' http-equiv=content-type>"), Some("utf-8"));
+/// assert_eq!(prescan(b"<meta content='text/html; charset=utf-8'>"), None);
+/// ```
+pub fn prescan(body: &[u8]) -> Option<&'static str> {
+    let bytes = &body[..body.len().min(1024)];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        if rest.starts_with(b"<!--") {
+            // comment: go to the end of "-->" (its dashes may be the ones of "<!--",
+            // so "<!-->" and "<!--->" are complete comments)
+            pos = find_subsequence(bytes, pos + 2, b"-->").map_or(bytes.len(), |idx| idx + 2);
+        } else if rest.len() > 5
+            && rest[..5].eq_ignore_ascii_case(b"<meta")
+            && (is_space(rest[5]) || rest[5] == b'/')
+        {
+            pos += 6;
+            if let Some(encoding) = meta_charset(bytes, &mut pos) {
+                return Some(encoding);
+            }
+        } else if rest.len() > 2
+            && rest[0] == b'<'
+            && (rest[1].is_ascii_alphabetic() || (rest[1] == b'/' && rest[2].is_ascii_alphabetic()))
+        {
+            // other tag: skip its name and attributes
+            pos += rest
+                .iter()
+                .position(|&b| is_space(b) || b == b'>')
+                .unwrap_or(rest.len());
+            while get_attribute(bytes, &mut pos).is_some() {}
+        } else if rest.starts_with(b"<!") || rest.starts_with(b"</") || rest.starts_with(b"<?") {
+            pos = find_subsequence(bytes, pos + 2, b">").unwrap_or(bytes.len());
+        }
+        pos += 1;
+    }
+    None
+}
+
+// Process attributes of meta tag and return its charset (if the tag declares it)
+fn meta_charset(bytes: &[u8], pos: &mut usize) -> Option<&'static str> {
+    let mut attribute_list: Vec<Vec<u8>> = vec![];
+    let mut got_pragma = false;
+    let mut need_pragma: Option<bool> = None;
+    let mut charset: Option<Option<&'static str>> = None;
+
+    while let Some((name, value)) = get_attribute(bytes, pos) {
+        if attribute_list.contains(&name) {
+            continue;
+        }
+        match name.as_slice() {
+            b"http-equiv" => got_pragma |= value == b"content-type",
+            b"content" if charset.is_none() => {
+                if let Some(label) = charset_from_content(&value) {
+                    charset = Some(get_encoding(label));
+                    need_pragma = Some(true);
+                }
+            }
+            b"charset" => {
+                charset = Some(get_encoding(&value));
+                need_pragma = Some(false);
+            }
+            _ => {}
+        }
+        attribute_list.push(name);
+    }
+
+    // incomplete tag at the end of prescanned bytes
+    if *pos >= bytes.len() {
+        return None;
+    }
+    match need_pragma {
+        None => return None,
+        Some(true) if !got_pragma => return None,
+        _ => {}
+    }
+    match charset.flatten()? {
+        "utf-16be" | "utf-16le" => Some("utf-8"),
+        "x-user-defined" => Some("windows-1252"),
+        charset => supported_encodings()
+            .into_iter()
+            .find(|&enc| enc == charset),
+    }
+}
+
+// Get an attribute (lowercased name & value), position is moved after it.
+// None means there are no more attributes in the tag.
+fn get_attribute(bytes: &[u8], pos: &mut usize) -> Option<(Vec<u8>, Vec<u8>)> {
+    let byte_at = |pos: usize| bytes.get(pos).copied();
+    while byte_at(*pos).is_some_and(|b| is_space(b) || b == b'/') {
+        *pos += 1;
+    }
+    if byte_at(*pos) == Some(b'>') {
+        return None;
+    }
+
+    // attribute name
+    let mut name: Vec<u8> = vec![];
+    loop {
+        match byte_at(*pos)? {
+            b'=' if !name.is_empty() => break,
+            b if is_space(b) => {
+                while byte_at(*pos).is_some_and(is_space) {
+                    *pos += 1;
+                }
+                if byte_at(*pos)? != b'=' {
+                    return Some((name, vec![]));
+                }
+                break;
+            }
+            b'/' | b'>' => return Some((name, vec![])),
+            b => name.push(b.to_ascii_lowercase()),
+        }
+        *pos += 1;
+    }
+
+    // attribute value (position is at "=")
+    *pos += 1;
+    while byte_at(*pos).is_some_and(is_space) {
+        *pos += 1;
+    }
+    let mut value: Vec<u8> = vec![];
+    match byte_at(*pos)? {
+        quote @ (b'"' | b'\'') => loop {
+            *pos += 1;
+            match byte_at(*pos)? {
+                b if b == quote => {
+                    *pos += 1;
+                    return Some((name, value));
+                }
+                b => value.push(b.to_ascii_lowercase()),
+            }
+        },
+        b'>' => Some((name, value)),
+        _ => loop {
+            match byte_at(*pos)? {
+                b if is_space(b) || b == b'>' => return Some((name, value)),
+                b => value.push(b.to_ascii_lowercase()),
+            }
+            *pos += 1;
+        },
+    }
+}
+
+// Extract charset from content attribute of meta tag (f.e. "text/html; charset=utf-8")
+fn charset_from_content(content: &[u8]) -> Option<&[u8]> {
+    let mut pos = 0;
+    loop {
+        pos = find_subsequence(content, pos, b"charset")? + 7;
+        while content.get(pos).copied().is_some_and(is_space) {
+            pos += 1;
+        }
+        if content.get(pos) != Some(&b'=') {
+            continue;
+        }
+        pos += 1;
+        while content.get(pos).copied().is_some_and(is_space) {
+            pos += 1;
+        }
+        return match *content.get(pos)? {
+            quote @ (b'"' | b'\'') => {
+                let end = find_subsequence(content, pos + 1, &[quote])?;
+                Some(&content[pos + 1..end])
+            }
+            _ => {
+                let end = content[pos..]
+                    .iter()
+                    .position(|&b| is_space(b) || b == b';')
+                    .map_or(content.len(), |len| pos + len);
+                Some(&content[pos..end])
+            }
+        };
+    }
+}
+
+// Encoding by WHATWG label
+fn get_encoding(label: &[u8]) -> Option<&'static str> {
+    let encoding = encoding_from_whatwg_label(std::str::from_utf8(label).ok()?)?;
+    Some(encoding.whatwg_name().unwrap_or(encoding.name()))
+}
+
+fn is_space(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
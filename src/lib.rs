@@ -19,10 +19,18 @@
 //!
 //! # Library:
 //!
-//! Library offers two main methods:
+//! Library offers three main methods:
 //!
 //! * `from_bytes` processes text using bytes as input parameter
 //! * `from_path` processes text using filename as input parameter
+//! * `from_reader` processes text from any `std::io::Read` source
+//!
+//! Many files can be processed at once by a pool of threads with `from_paths`.
+//!
+//! If data comes in pieces (sockets, pipes), `stream::StreamingDetector` can be fed incrementally.
+//! For many detections with the same settings, `detector::Detector` validates and prepares them once.
+//! To find out why some encoding was chosen over another one, use `from_bytes_with_report`.
+//! Web content can be sniffed in the same order as browsers do with `sniff_html`.
 //!
 //! ## Examples:
 //!
@@ -128,31 +136,46 @@
 //!     "is_preferred": true
 //! }
 //! ```
-use crate::cd::{
-    coherence_ratio, encoding_languages, mb_encoding_languages, merge_coherence_ratios,
-};
-use crate::consts::{IANA_SUPPORTED, MAX_PROCESSED_BYTES, TOO_BIG_SEQUENCE, TOO_SMALL_SEQUENCE};
-use crate::entity::{CharsetMatch, CharsetMatches, CoherenceMatches, NormalizerSettings};
-use crate::md::mess_ratio;
+use crate::consts::{TOO_BIG_SEQUENCE, TOO_SMALL_SEQUENCE};
+use crate::declarations::{find_declarations, unambiguous_encoding};
+use crate::detector::Detector;
+use crate::entity::{CharsetMatch, CharsetMatches, ContentMode, NormalizerSettings};
+use crate::error::Error;
+use crate::html::{bom_encoding, prescan, transport_encoding, HtmlSniffing, SniffingStep};
+use crate::markup::{looks_like_markup, strip_markup};
+use crate::probe::{Analysis, Prober};
+use crate::report::{CandidateOutcome, DetectionReport, Utf7Verdict};
 use crate::utils::{
-    any_specified_encoding, decode, iana_name, identify_sig_or_bom, is_cp_similar,
-    is_invalid_chunk, is_multi_byte_encoding,
+    identify_ebcdic, identify_oem, identify_sig_or_bom, identify_utf16_without_bom,
+    identify_utf32_without_bom, identify_utf8_variants, is_ebcdic_encoding, is_oem_code_page,
+    utf7_verdict,
 };
-use encoding::DecoderTrap;
 use log::{debug, trace};
-use std::borrow::Cow;
+use ordered_float::OrderedFloat;
+use std::any::Any;
 use std::collections::VecDeque;
-use std::fs::File;
 use std::io::Read;
-use std::path::Path;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 
 pub mod assets;
 // TODO: Revisit float conversions when we want to push for accuracy
 #[allow(clippy::cast_lossless, clippy::cast_precision_loss)]
 mod cd;
+pub mod codecs;
 pub mod consts;
+pub mod declarations;
+pub mod detector;
 pub mod entity;
-mod md;
+pub mod error;
+pub mod html;
+mod markup;
+pub mod md;
+mod probe;
+pub mod report;
+pub mod stream;
 mod tests;
 pub mod utils;
 
@@ -174,65 +197,167 @@ pub mod utils;
 pub fn from_bytes(
     bytes: &[u8],
     settings: Option<NormalizerSettings>,
-) -> Result<CharsetMatches, String> {
-    // init settings with default values if it's None and recheck include_encodings and
-    // exclude_encodings settings
-    let mut settings = settings.unwrap_or_default();
-    if !settings.include_encodings.is_empty() {
-        let mut normalized = vec![];
-        for enc in &settings.include_encodings {
-            normalized.push(
-                iana_name(enc)
-                    .ok_or_else(|| format!("included {enc} is not a valid encoding name"))?
-                    .to_string(),
-            );
+) -> Result<CharsetMatches, Error> {
+    detect(bytes, settings, false)
+}
+
+/// Same thing than the function from_bytes, but also returns report which explains
+/// the fate of every supported encoding: why it was excluded, failed or accepted,
+/// which encodings were prioritized and which fallback was used.
+///
+/// ```rust
+/// use charset_normalizer_rs::from_bytes_with_report;
+/// use charset_normalizer_rs::report::CandidateOutcome;
+///
+/// let (result, report) = from_bytes_with_report("Qu'est ce que une étoile?".as_bytes(), None).unwrap();
+/// assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+/// assert_eq!(report.early_return.as_deref(), Some("utf-8"));
+/// assert!(matches!(report.outcome("ascii"), Some(CandidateOutcome::HardFailure { offset: 18 })));
+/// ```
+pub fn from_bytes_with_report(
+    bytes: &[u8],
+    settings: Option<NormalizerSettings>,
+) -> Result<(CharsetMatches, DetectionReport), Error> {
+    Detector::new(settings.unwrap_or_default())?.detect_with_report(bytes)
+}
+
+/// Encoding sniffing of HTML document in the same order as browsers do: byte order mark,
+/// then charset of transport layer (`Content-Type` header, only WHATWG labels are known),
+/// then prescan of `<meta>` tags in the first 1024 bytes, and statistical detection only
+/// if none of them has given an encoding. Result tells which step has decided the encoding.
+///
+/// Unlike browsers, encoding which does not fit the body at all (or is not allowed by
+/// `include_encodings` / `exclude_encodings` settings) is skipped, and next step is used.
+///
+/// ```rust
+/// use charset_normalizer_rs::html::SniffingStep;
+/// use charset_normalizer_rs::sniff_html;
+///
+/// let body = b"<html><head><meta charset=\"windows-1251\"></head><body>\xcf\xf0\xe8\xe2\xe5\xf2</body></html>";
+/// let result = sniff_html(body, Some("text/html"), None).unwrap();
+/// assert_eq!(result.decided_by, SniffingStep::MetaPrescan);
+/// assert_eq!(result.matches.get_best().unwrap().encoding(), "windows-1251");
+/// ```
+pub fn sniff_html(
+    body: &[u8],
+    content_type: Option<&str>,
+    settings: Option<NormalizerSettings>,
+) -> Result<HtmlSniffing, Error> {
+    let detector = Detector::new(settings.unwrap_or_default())?;
+    let steps = [
+        (SniffingStep::ByteOrderMark, bom_encoding(body)),
+        (
+            SniffingStep::TransportLayer,
+            content_type.and_then(transport_encoding),
+        ),
+        (SniffingStep::MetaPrescan, prescan(body)),
+    ];
+    for (step, encoding) in steps {
+        let Some(encoding) = encoding else {
+            continue;
+        };
+        if !detector.encodings().contains(&encoding) {
+            trace!("{:?} gives {}, which is not allowed.", step, encoding);
+            continue;
         }
-        settings.include_encodings = normalized;
-        trace!(
-            "include_encodings is set. Use this flag for debugging purpose. \
-        Limited list of encoding allowed : {}.",
-            settings.include_encodings.join(", ")
-        );
-    }
-    if !settings.exclude_encodings.is_empty() {
-        let mut normalized = vec![];
-        for enc in &settings.exclude_encodings {
-            normalized.push(
-                iana_name(enc)
-                    .ok_or_else(|| format!("excluded encoding {enc} is not a valid encoding name"))?
-                    .to_string(),
+
+        // decided encoding is only measured (any amount of chaos is allowed)
+        let settings = NormalizerSettings {
+            include_encodings: vec![encoding.to_string()],
+            exclude_encodings: vec![],
+            threshold: OrderedFloat(1.0),
+            preemptive_behaviour: false,
+            hints: Default::default(),
+            ..detector.settings().clone()
+        };
+        let matches = detect(body, Some(settings), false)?;
+        if matches.is_empty() {
+            trace!(
+                "{:?} gives {}, which does not fit the body.",
+                step,
+                encoding
             );
+            continue;
         }
-        settings.exclude_encodings = normalized;
-        trace!(
-            "exclude_encodings is set. Use this flag for debugging purpose. \
-        Limited list of encoding allowed : {}.",
-            settings.exclude_encodings.join(", ")
+        debug!(
+            "HTML encoding sniffing: {} is decided by {:?}.",
+            encoding, step
         );
+        return Ok(HtmlSniffing {
+            decided_by: step,
+            matches,
+        });
     }
 
+    detector.detect(body).map(|matches| HtmlSniffing {
+        decided_by: SniffingStep::Detection,
+        matches,
+    })
+}
+
+// Detection workflow behind from_bytes with not prepared settings.
+// If is_truncated is set, given bytes are only a prefix of a longer sequence (f.e. data fed
+// to StreamingDetector so far), so an incomplete multi-byte sequence at the very end of it
+// should not disqualify an encoding.
+pub(crate) fn detect(
+    bytes: &[u8],
+    settings: Option<NormalizerSettings>,
+    is_truncated: bool,
+) -> Result<CharsetMatches, Error> {
+    detect_with(
+        &Detector::new(settings.unwrap_or_default())?,
+        bytes,
+        is_truncated,
+    )
+    .map(|(results, _)| results)
+}
+
+// Detection workflow with prepared settings, matches over truncated payload are marked as such
+pub(crate) fn detect_with(
+    detector: &Detector,
+    bytes: &[u8],
+    is_truncated: bool,
+) -> Result<(CharsetMatches, DetectionReport), Error> {
+    let (mut results, report) = detect_payload(detector, bytes, is_truncated)?;
+    if is_truncated {
+        results.set_truncated();
+    }
+    Ok((results, report))
+}
+
+fn detect_payload(
+    detector: &Detector,
+    bytes: &[u8],
+    is_truncated: bool,
+) -> Result<(CharsetMatches, DetectionReport), Error> {
+    let settings = detector.settings();
+    let mut steps = settings.steps;
+    let mut chunk_size = settings.chunk_size;
+    let mut report = DetectionReport::default();
+
     // check for empty
     let bytes_length = bytes.len();
     if bytes_length == 0 {
         debug!("Encoding detection on empty bytes, assuming utf_8 intention.");
-        return Ok(CharsetMatches::from_single(CharsetMatch::default()));
+        report.complete(detector.encodings(), &[]);
+        return Ok((CharsetMatches::from_single(CharsetMatch::default()), report));
     }
 
     // check min length
-    if bytes_length <= (settings.chunk_size * settings.steps) {
+    if bytes_length <= (chunk_size * steps) {
         trace!(
             "override steps ({}) and chunk_size ({}) as content does not \
             fit ({} byte(s) given) parameters.",
-            settings.steps,
-            settings.chunk_size,
+            steps,
+            chunk_size,
             bytes_length,
         );
-        settings.steps = 1;
-        settings.chunk_size = bytes_length;
+        steps = 1;
+        chunk_size = bytes_length;
     }
 
-    if settings.steps > 1 && bytes_length / settings.steps < settings.chunk_size {
-        settings.chunk_size = bytes_length / settings.steps;
+    if steps > 1 && bytes_length / steps < chunk_size {
+        chunk_size = bytes_length / steps;
     }
 
     // too small length
@@ -258,7 +383,16 @@ pub fn from_bytes(
     // search for encoding in the content
     let mut specified_encoding: String = String::new();
     if settings.preemptive_behaviour {
-        if let Some(enc) = any_specified_encoding(bytes, 4096) {
+        report.declarations = find_declarations(bytes, 4096);
+        for declaration in report.declarations.iter().filter(|d| !d.unambiguous) {
+            trace!(
+                "Declarative mark {:?} ({:?}) is out of its usual place, ignoring it.",
+                declaration.label,
+                declaration.syntax,
+            );
+        }
+        // only unambiguous declarations are trusted
+        if let Some(enc) = unambiguous_encoding(&report.declarations) {
             trace!(
                 "Detected declarative mark in sequence. Priority +1 given for {}.",
                 &enc
@@ -279,18 +413,125 @@ pub fn from_bytes(
         prioritized_encodings.push(sig_enc);
     }
 
+    // 7-bit content goes to utf-7 before ascii only if its shift sequences are plausible
+    report.utf7_verdict = utf7_verdict(bytes, settings.threshold, &settings.mess_detector);
+    let is_utf7_plausible = matches!(report.utf7_verdict, Some(Utf7Verdict::Plausible { .. }));
+    if let Some(verdict) = &report.utf7_verdict {
+        trace!("Content is 7-bit, utf-7 verdict: {:?}.", verdict);
+    }
+    if is_utf7_plausible {
+        prioritized_encodings.push("utf-7");
+    }
+
+    // utf-8 variants are tested (before ascii) only if encoded surrogates or overlong NUL are found
+    let utf8_variants = identify_utf8_variants(bytes);
+    if !utf8_variants.is_empty() {
+        trace!(
+            "Byte patterns point to {}. Priority +1 given for them.",
+            utf8_variants.join(", ")
+        );
+        prioritized_encodings.extend(&utf8_variants);
+    }
+
     // add ascii & utf-8
     prioritized_encodings.extend(&["ascii", "utf-8"]);
+    report.prioritized_encodings = prioritized_encodings
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    report.specified_encoding =
+        (!specified_encoding.is_empty()).then(|| specified_encoding.clone());
+    report.sig_encoding = sig_encoding.clone();
 
     // generate array of encodings for probing with prioritizing
-    let mut iana_encodings: VecDeque<&str> = VecDeque::from(IANA_SUPPORTED.clone());
+    let mut iana_encodings: VecDeque<&'static str> = VecDeque::from(detector.encodings().to_vec());
+    let mut prioritized_count: usize = 0;
     for pe in prioritized_encodings.iter().rev() {
-        if let Some(index) = iana_encodings.iter().position(|x| x == pe) {
-            let value = iana_encodings.remove(index).expect("index found above");
+        if let Some(value) = iana_encodings
+            .iter()
+            .position(|x| x == pe)
+            .and_then(|index| iana_encodings.remove(index))
+        {
             iana_encodings.push_front(value);
+            prioritized_count += 1;
         }
     }
 
+    // utf-16 & utf-32 without BOM are tested only if their byte patterns point to them
+    // (parity of NUL bytes for utf-16, code points validity for utf-32)
+    let unicode_without_bom: Vec<&'static str> = match sig_encoding {
+        None => [
+            identify_utf16_without_bom(bytes),
+            identify_utf32_without_bom(bytes),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        Some(_) => vec![],
+    };
+    for enc in &unicode_without_bom {
+        trace!(
+            "Byte patterns point to {} without BOM, it will be tested.",
+            enc
+        );
+    }
+    // EBCDIC code pages are tested only if byte distribution points to them
+    let is_ebcdic = identify_ebcdic(bytes);
+    if is_ebcdic {
+        trace!("Byte distribution points to EBCDIC, its code pages will be tested.");
+    }
+    // DOS code pages never fail decoding, so they are tested only if bytes point to them
+    let is_oem = identify_oem(bytes);
+    if is_oem {
+        trace!("Byte distribution points to DOS, its code pages will be tested.");
+    }
+    let mut bom_required: Vec<&'static str> = vec![];
+    iana_encodings.retain(|&encoding_iana| {
+        let requires_bom = match encoding_iana {
+            "utf-16le" | "utf-16be" | "utf-32le" | "utf-32be" => {
+                sig_encoding.as_deref() != Some(encoding_iana)
+                    && !unicode_without_bom.contains(&encoding_iana)
+            }
+            "utf-7" => !is_utf7_plausible && specified_encoding != encoding_iana,
+            "cesu-8" | "mutf-8" | "wtf-8" => {
+                !utf8_variants.contains(&encoding_iana) && specified_encoding != encoding_iana
+            }
+            e if is_ebcdic_encoding(e) => !is_ebcdic && specified_encoding != encoding_iana,
+            e if is_oem_code_page(e) => !is_oem && specified_encoding != encoding_iana,
+            _ => false,
+        };
+        if requires_bom {
+            trace!(
+                "Encoding {} won't be tested as-is because it require a BOM or specific byte patterns.",
+                encoding_iana,
+            );
+            bom_required.push(encoding_iana);
+        }
+        !requires_bom
+    });
+    let prioritized_count = prioritized_count.min(iana_encodings.len());
+
+    // encodings hinted by caller go right after prioritized ones, the most weighted first
+    let mut hinted_encodings: Vec<String> = vec![];
+    for &(hinted, _) in detector.priors().iter().rev() {
+        if let Some(value) = iana_encodings
+            .iter()
+            .skip(prioritized_count)
+            .position(|&x| x == hinted)
+            .and_then(|index| iana_encodings.remove(prioritized_count + index))
+        {
+            iana_encodings.insert(prioritized_count, value);
+            hinted_encodings.insert(0, value.to_string());
+        }
+    }
+    if !hinted_encodings.is_empty() {
+        trace!(
+            "Encodings hinted by caller will be probed next: {}.",
+            hinted_encodings.join(", ")
+        );
+    }
+    report.hinted_encodings = hinted_encodings;
+
     // Main processing loop variables
     let mut tested_but_hard_failure: Vec<&str> = vec![];
     let mut tested_but_soft_failure: Vec<&str> = vec![];
@@ -299,264 +540,189 @@ pub fn from_bytes(
     let mut fallback_specified: Option<CharsetMatch> = None;
     let mut results: CharsetMatches = CharsetMatches::default();
 
-    let bytes: Cow<'static, [u8]> = Cow::Owned(bytes.to_vec());
+    // markup is stripped before chunking
+    let is_markup = match settings.content_mode {
+        ContentMode::PlainText => false,
+        ContentMode::Markup => true,
+        ContentMode::Auto => looks_like_markup(bytes),
+    };
+    if is_markup {
+        trace!("Markup will be stripped before mess & coherence probing.");
+    }
+    report.is_markup = is_markup;
+
+    let prober = Prober {
+        detector,
+        // all matches share the same copy of input
+        bytes: Arc::from(bytes),
+        steps,
+        chunk_size,
+        sig_encoding: sig_encoding.as_deref(),
+        sig_len: sig_payload.map(|sig_pay| sig_pay.len()).unwrap_or_default(),
+        is_too_large_sequence,
+        is_truncated,
+        is_markup,
+        stripped_bytes: (is_markup && is_too_large_sequence)
+            .then(|| strip_markup(bytes, false))
+            .filter(|stripped| !stripped.content.trim_ascii().is_empty()),
+    };
+
+    // Prioritized encodings are probed one by one, as one of them most likely wins early.
+    // With "parallel" feature the rest of them are probed concurrently in waves (see
+    // probing_wave), and their outcomes are replayed below in the same order, so results
+    // are identical to sequential probing.
+    let iana_encodings: Vec<&str> = iana_encodings.into();
+    #[cfg(feature = "parallel")]
+    let mut probed: ahash::HashMap<&str, Result<Analysis, Error>> = Default::default();
 
     // Iterate and probe our encodings
-    'iana_encodings_loop: for encoding_iana in iana_encodings {
-        if (!settings.include_encodings.is_empty()
-            && !settings
-                .include_encodings
-                .contains(&encoding_iana.to_string()))
-            || settings
-                .exclude_encodings
-                .contains(&encoding_iana.to_string())
-        {
-            continue;
-        }
-        let bom_or_sig_available: bool = sig_encoding.as_deref() == Some(encoding_iana);
-        // let strip_sig_or_bom = true // unlike python version this is always true in rust
-        let is_multi_byte_decoder: bool = is_multi_byte_encoding(encoding_iana);
-
-        // utf-16le & utf-16be cannot be identified without BOM
-        if !bom_or_sig_available && ["utf-16le", "utf-16be"].contains(&encoding_iana) {
-            trace!(
-                "Encoding {} won't be tested as-is because it require a BOM. Will try some sub-encoder LE/BE",
-                encoding_iana,
+    let mut pending: &[&str] = &iana_encodings;
+    'iana_encodings_loop: while let Some((&encoding_iana, rest)) = pending.split_first() {
+        #[cfg(feature = "parallel")]
+        if !prioritized_encodings.contains(&encoding_iana) && !probed.contains_key(encoding_iana) {
+            use rayon::prelude::*;
+            let wave = probing_wave(detector, pending, &tested_but_soft_failure);
+            probed.extend(
+                wave.par_iter()
+                    .map(|&encoding_iana| (encoding_iana, prober.probe(encoding_iana)))
+                    .collect::<Vec<_>>(),
             );
-            continue;
         }
-
-        // fast pre-check
-        let start_idx = match bom_or_sig_available {
-            true => sig_payload
-                .ok_or_else(|| "sig_payload cannot not be None".to_string())?
-                .len(),
-            false => 0,
+        pending = rest;
+        #[cfg(feature = "parallel")]
+        let probe = probed.remove(encoding_iana);
+        #[cfg(not(feature = "parallel"))]
+        let probe: Option<Result<Analysis, Error>> = None;
+
+        // fast pre-check (if encoding was not probed yet)
+        let (precheck, analysis) = match probe {
+            Some(Ok(analysis)) => (Ok(None), Some(analysis)),
+            Some(Err(err)) => (Err(err), None),
+            None => (prober.precheck(encoding_iana), None),
         };
-        let end_idx = match is_too_large_sequence && !is_multi_byte_decoder {
-            true => MAX_PROCESSED_BYTES,
-            false => bytes_length,
-        };
-        let decoded_payload: Option<String> = if let Ok(payload) = decode(
-            &bytes[start_idx..end_idx],
-            encoding_iana,
-            DecoderTrap::Strict,
-            is_too_large_sequence && !is_multi_byte_decoder,
-            false,
-        ) {
-            (!is_too_large_sequence || is_multi_byte_decoder).then_some(payload)
-        } else {
-            trace!(
-                "Code page {} does not fit given bytes sequence at ALL.",
-                encoding_iana,
-            );
-            tested_but_hard_failure.push(encoding_iana);
-            continue 'iana_encodings_loop;
+        let decoded_payload = match precheck {
+            Ok(decoded_payload) => decoded_payload,
+            Err(err) => {
+                tested_but_hard_failure.push(encoding_iana);
+                let offset = match err {
+                    Error::Decode { offset, .. } => offset,
+                    _ => 0,
+                };
+                report.add(encoding_iana, CandidateOutcome::HardFailure { offset });
+                continue 'iana_encodings_loop;
+            }
         };
 
         // soft failed pre-check
         // important thing! it occurs sometimes fail detection
         for encoding_soft_failed in &tested_but_soft_failure {
-            if is_cp_similar(encoding_iana, encoding_soft_failed) {
+            if detector.is_cp_similar(encoding_iana, encoding_soft_failed) {
                 trace!("{} is deemed too similar to code page {} and was consider unsuited already. Continuing!",
                     encoding_iana,
                     encoding_soft_failed,
                 );
+                report.add(
+                    encoding_iana,
+                    CandidateOutcome::SimilarToSoftFailure {
+                        similar_to: encoding_soft_failed.to_string(),
+                    },
+                );
                 continue 'iana_encodings_loop;
             }
         }
 
-        // lets split input by chunks and try to parse them
-        let max_chunk_gave_up = 2.max(settings.steps / 4);
-        let mut early_stop_count: usize = 0;
-        let mut lazy_str_hard_failure = false;
-        let mut md_ratios: Vec<f32> = vec![];
-
-        // detect target languages
-        let target_languages = if is_multi_byte_decoder {
-            mb_encoding_languages(encoding_iana)
-        } else {
-            encoding_languages(encoding_iana.to_string())
+        // measure mess and coherence
+        let analysis = match analysis {
+            Some(analysis) => analysis,
+            None => prober.analyze(encoding_iana, decoded_payload),
         };
-        trace!(
-            "{} should target any language(s) of {:?}",
-            encoding_iana,
-            target_languages,
-        );
-
-        // main loop over chunks in our input
-        // we go over bytes or chars - it depends on previous code
-        let seq_len = match &decoded_payload {
-            Some(payload) => payload.chars().count(),
-            None => bytes_length,
-        };
-        let starting_offset = match (bom_or_sig_available, &decoded_payload) {
-            (true, None) => start_idx,
-            _ => 0,
-        };
-        let offsets = (starting_offset..seq_len).step_by((seq_len / settings.steps).max(1));
-
-        // Chunks Loop
-        // Iterate over chunks of bytes or chars
-        let mut md_chunks: Vec<String> = vec![];
-        'chunks_loop: for offset in offsets {
-            let decoded_chunk_result = match &decoded_payload {
-                // Chars processing
-                Some(payload) => Ok(payload
-                    .chars()
-                    .skip(offset)
-                    .take(settings.chunk_size)
-                    .collect()),
-                // Bytes processing
-                None => decode(
-                    &bytes[offset..(offset + settings.chunk_size).min(seq_len)],
-                    encoding_iana,
-                    DecoderTrap::Strict,
-                    false,
-                    false,
-                ),
-            };
-
-            if is_invalid_chunk(&decoded_chunk_result, encoding_iana) {
-                trace!(
-                    "LazyStr Loading: After MD chunk decode, code page {} \
-                    does not fit given bytes sequence at ALL. {}",
+        let charset_match = match analysis {
+            Analysis::HardFailure { offset } => {
+                tested_but_hard_failure.push(encoding_iana);
+                report.add(encoding_iana, CandidateOutcome::HardFailure { offset });
+                continue 'iana_encodings_loop;
+            }
+            Analysis::SoftFailure {
+                md_ratios,
+                decoded_payload,
+                failed_at,
+            } => {
+                tested_but_soft_failure.push(encoding_iana);
+                report.add(
                     encoding_iana,
-                    match decoded_chunk_result {
-                        Ok(_) => String::from("non-ascii"),
-                        Err(message) => message.to_string(),
+                    match failed_at {
+                        // code page does not fit one of chunks
+                        Some(offset) => CandidateOutcome::HardFailure { offset },
+                        None => CandidateOutcome::SoftFailure {
+                            mess_ratios: md_ratios,
+                        },
                     },
                 );
-                early_stop_count = max_chunk_gave_up;
-                lazy_str_hard_failure = true;
-                break 'chunks_loop;
-            }
-            let decoded_chunk = decoded_chunk_result?;
-
-            // MD ratios calc
-            md_chunks.push(decoded_chunk.clone());
-            md_ratios.push(mess_ratio(decoded_chunk, Some(settings.threshold)));
-            if md_ratios.last().expect("never empty") >= &settings.threshold {
-                early_stop_count += 1;
-            }
-            if early_stop_count >= max_chunk_gave_up {
-                break 'chunks_loop;
+                // Preparing those fallbacks in case we got nothing.
+                if settings.enable_fallback
+                    && failed_at.is_none()
+                    && prioritized_encodings.contains(&encoding_iana)
+                {
+                    let fallback_entry = detector.codec(encoding_iana).ok().map(|codec| {
+                        CharsetMatch::new(
+                            prober.bytes.clone(),
+                            encoding_iana,
+                            f32::from(settings.threshold),
+                            false,
+                            &vec![],
+                            decoded_payload.filter(|_| !is_too_large_sequence),
+                        )
+                        .with_prior(detector.prior(encoding_iana))
+                        .with_codec(codec)
+                    });
+
+                    report.prepared_fallbacks.push(encoding_iana.to_string());
+                    match encoding_iana {
+                        e if e == specified_encoding => fallback_specified = fallback_entry,
+                        "ascii" => fallback_ascii = fallback_entry,
+                        _ => fallback_u8 = fallback_entry,
+                    }
+                }
+                continue 'iana_encodings_loop;
             }
-        }
-
-        // We might want to check the remainder of sequence
-        // Only if initial MD tests passes
-        if !lazy_str_hard_failure && is_too_large_sequence && !is_multi_byte_decoder {
-            let decoded_chunk_result = decode(
-                &bytes[MAX_PROCESSED_BYTES..],
-                encoding_iana,
-                DecoderTrap::Strict,
-                false,
-                false,
-            );
-            if is_invalid_chunk(&decoded_chunk_result, encoding_iana) {
-                trace!(
-                    "LazyStr Loading: After final lookup, code page {} does not fit \
-                    given bytes sequence at ALL. {}",
+            Analysis::Passed {
+                md_ratios,
+                charset_match,
+            } => {
+                report.add(
                     encoding_iana,
-                    decoded_chunk_result.unwrap_err()
+                    CandidateOutcome::Accepted {
+                        mess_ratios: md_ratios,
+                        mean_mess_ratio: charset_match.chaos(),
+                        coherence_matches: charset_match
+                            .coherence_matches()
+                            .iter()
+                            .map(|cm| (cm.language, cm.score.0))
+                            .collect(),
+                    },
                 );
-                tested_but_hard_failure.push(encoding_iana);
-                continue 'iana_encodings_loop;
+                charset_match
             }
-        }
-
-        // process mean mess ratio
-        let mean_mess_ratio = match md_ratios.is_empty() {
-            true => 0.0,
-            false => md_ratios.iter().sum::<f32>() / (md_ratios.len() as f32),
         };
 
-        if mean_mess_ratio >= *settings.threshold || early_stop_count >= max_chunk_gave_up {
-            tested_but_soft_failure.push(encoding_iana);
-            trace!(
-                "{} was excluded because of initial chaos probing. \
-                Gave up {} time(s). Computed mean chaos is {} %.",
-                encoding_iana,
-                early_stop_count,
-                mean_mess_ratio * 100.0,
-            );
-            // Preparing those fallbacks in case we got nothing.
-            if settings.enable_fallback
-                && !lazy_str_hard_failure
-                && prioritized_encodings.contains(&encoding_iana)
-            {
-                let fallback_entry = Some(CharsetMatch::new(
-                    bytes.clone(),
-                    encoding_iana,
-                    f32::from(settings.threshold),
-                    false,
-                    &vec![],
-                    decoded_payload.as_deref(),
-                ));
-
-                match encoding_iana {
-                    e if e == specified_encoding => fallback_specified = fallback_entry,
-                    "ascii" => fallback_ascii = fallback_entry,
-                    _ => fallback_u8 = fallback_entry,
-                }
-            }
-            continue 'iana_encodings_loop;
-        }
-        trace!(
-            "{} passed initial chaos probing. Mean measured chaos is {} %",
-            encoding_iana,
-            mean_mess_ratio * 100.0,
-        );
-
-        // CD rations calc
-        // We shall skip the CD when its about ASCII
-        // Most of the time its not relevant to run "language-detection" on it.
-        let mut cd_ratios: Vec<CoherenceMatches> = vec![];
-        if encoding_iana != "ascii" {
-            cd_ratios.extend(md_chunks.iter().filter_map(|chunk| {
-                coherence_ratio(
-                    chunk.clone(),
-                    Some(settings.language_threshold),
-                    Some(target_languages.clone()),
-                )
-                .ok()
-            }));
-        }
-
-        // process cd ratios
-        let cd_ratios_merged = merge_coherence_ratios(&cd_ratios);
-        if !cd_ratios_merged.is_empty() {
-            trace!(
-                "We detected language {:?} using {}",
-                cd_ratios_merged,
-                encoding_iana
-            );
-        }
-
         // process results
-        results.append(CharsetMatch::new(
-            bytes.clone(),
-            encoding_iana,
-            mean_mess_ratio,
-            bom_or_sig_available,
-            &cd_ratios_merged,
-            decoded_payload.as_deref(),
-        ));
+        let mean_mess_ratio = charset_match.chaos();
+        results.append(charset_match);
 
+        // utf-8 variants which fit the whole content are as sure as SIG/BOM
         if (mean_mess_ratio < 0.1 && prioritized_encodings.contains(&encoding_iana))
             || encoding_iana == sig_encoding.clone().unwrap_or_default()
+            || utf8_variants.contains(&encoding_iana)
         {
-            debug!(
-                "Encoding detection: {} is most likely the one.",
-                encoding_iana
-            );
-            return Ok(CharsetMatches::from_single(
-                results
-                    .get_by_encoding(encoding_iana)
-                    .ok_or_else(|| format!("{encoding_iana} entry not present"))?
-                    .clone(),
-            ));
+            if let Some(found) = results.get_by_encoding(encoding_iana) {
+                debug!(
+                    "Encoding detection: {} is most likely the one.",
+                    encoding_iana
+                );
+                report.early_return = Some(encoding_iana.to_string());
+                report.complete(detector.encodings(), &bom_required);
+                return Ok((CharsetMatches::from_single(found.clone()), report));
+            }
         }
     }
 
@@ -578,42 +744,238 @@ pub fn from_bytes(
                 "Encoding detection: will be used as a fallback match {}",
                 fb_to_pass.encoding()
             );
+            report.used_fallback = Some(fb_to_pass.encoding().to_string());
             results.append(fb_to_pass.clone());
         };
     }
 
     // final logger information
-    if results.is_empty() {
-        debug!("Encoding detection: Unable to determine any suitable charset.");
-    } else {
-        debug!(
+    match results.get_best() {
+        None => debug!("Encoding detection: Unable to determine any suitable charset."),
+        Some(best_guess) => debug!(
             "Encoding detection: Found {} as plausible (best-candidate) for content. \
             With {} alternatives.",
-            results
-                .get_best()
-                .expect("not empty checked above")
-                .encoding(),
+            best_guess.encoding(),
             results.len() - 1,
-        );
+        ),
     }
-    Ok(results)
+    report.complete(detector.encodings(), &bom_required);
+    Ok((results, report))
 }
 
 /// Same thing than the function from_bytes but with one extra step.
 /// Opening and reading given file path in binary mode.
+/// The whole file is read, unless `max_read_bytes` is set: then only the first `max_read_bytes`
+/// bytes of it are processed and matches are marked by `CharsetMatch::is_truncated`.
 /// Can return Error.
 pub fn from_path(
     path: &Path,
     settings: Option<NormalizerSettings>,
-) -> Result<CharsetMatches, String> {
-    // read file
-    let mut file = File::open(path).map_err(|e| format!("Error opening file: {e}"))?;
-    let file_size = file.metadata().map(|m| m.len()).unwrap_or_default();
+) -> Result<CharsetMatches, Error> {
+    Detector::new(settings.unwrap_or_default())?.detect_path(path)
+}
+
+/// Same thing than the function from_path but for many files at once.
+/// Files are processed by a pool of `concurrency` worker threads (at least one), every result
+/// is paired with its path and is yielded as soon as it is ready, so order of results may
+/// differ from order of paths. Failure on one file (even a panic during its detection) does not
+/// stop processing of others. All worker threads are joined once results are exhausted or
+/// the iterator is dropped; a panic of `paths` iterator is propagated to the caller.
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use charset_normalizer_rs::from_paths;
+///
+/// let paths = vec![
+///     PathBuf::from("src/tests/data/samples/sample-chinese.txt"),
+///     PathBuf::from("non-exists-file.txt"),
+/// ];
+/// for (path, result) in from_paths(paths, None, 2) {
+///     match result {
+///         Ok(matches) => assert_eq!(matches.get_best().unwrap().encoding(), "big5"),
+///         Err(_) => assert_eq!(path, PathBuf::from("non-exists-file.txt")),
+///     }
+/// }
+/// ```
+pub fn from_paths<I, P>(
+    paths: I,
+    settings: Option<NormalizerSettings>,
+    concurrency: usize,
+) -> impl Iterator<Item = (PathBuf, Result<CharsetMatches, Error>)>
+where
+    I: IntoIterator<Item = P>,
+    I::IntoIter: Send + 'static,
+    P: Into<PathBuf>,
+{
+    let concurrency = concurrency.max(1);
+    let paths = Mutex::new(paths.into_iter());
+    // bounded channel: workers wait if results are not consumed
+    let (sender, receiver) = mpsc::sync_channel(concurrency);
+    // workers are scoped, so the pool thread returns only when all of them are joined
+    let pool = thread::spawn(move || {
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..concurrency)
+                .map(|_| {
+                    let sender = sender.clone();
+                    let settings = settings.clone();
+                    let paths = &paths;
+                    scope.spawn(move || loop {
+                        // lock is released before processing of taken path, it is poisoned only
+                        // if paths iterator has panicked (that panic is propagated by the pool)
+                        let next_path = paths.lock().unwrap_or_else(|e| e.into_inner()).next();
+                        let Some(path) = next_path else {
+                            break;
+                        };
+                        let path: PathBuf = path.into();
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                            from_path(&path, settings.clone())
+                        }))
+                        .unwrap_or_else(|payload| Err(Error::Panic(panic_message(&*payload))));
+                        // stop if nobody waits for results anymore
+                        if sender.send((path, result)).is_err() {
+                            break;
+                        }
+                    })
+                })
+                .collect();
+            drop(sender);
+            workers
+                .into_iter()
+                .filter_map(|worker| worker.join().err())
+                .last()
+        })
+    });
+    PathResults {
+        receiver: Some(receiver),
+        pool: Some(pool),
+    }
+}
 
-    let mut buffer = Vec::with_capacity(file_size as usize);
-    file.read_to_end(&mut buffer)
-        .map_err(|e| format!("Error reading from file: {e}"))?;
+// Results of from_paths, pool of workers is joined once they are exhausted (or dropped)
+struct PathResults {
+    receiver: Option<mpsc::Receiver<(PathBuf, Result<CharsetMatches, Error>)>>,
+    pool: Option<thread::JoinHandle<Option<Box<dyn Any + Send>>>>,
+}
+
+impl Iterator for PathResults {
+    type Item = (PathBuf, Result<CharsetMatches, Error>);
 
-    // calculate
-    from_bytes(&buffer, settings)
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.receiver.as_ref().and_then(|r| r.recv().ok()) {
+            return Some(item);
+        }
+        // all workers are done
+        self.receiver = None;
+        let pool = self.pool.take()?;
+        if let Some(payload) = pool.join().unwrap_or_else(Some) {
+            panic::resume_unwind(payload);
+        }
+        None
+    }
+}
+
+impl Drop for PathResults {
+    fn drop(&mut self) {
+        // workers stop as soon as they can't send results anymore
+        self.receiver = None;
+        if let Some(pool) = self.pool.take() {
+            let _ = pool.join();
+        }
+    }
+}
+
+// Message of panic payload (usually &str or String)
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown panic"))
+}
+
+/// Same thing than the function from_bytes but payload is read from any `std::io::Read` source.
+/// If `max_read_bytes` is set (it is `None` by default), no more than this amount of bytes plus one
+/// is consumed from the reader: the extra byte tells if the stream goes on, and it is processed as
+/// well (then matches are marked by `CharsetMatch::is_truncated`). The rest of the stream stays in the reader. Pass reader by mutable reference
+/// (or use `Read::by_ref`) to keep streaming from it after detection. Raw bytes of the processed
+/// (consumed) part are available through `CharsetMatch::raw`.
+///
+/// ```rust
+/// use std::io::{Cursor, Read};
+/// use charset_normalizer_rs::entity::NormalizerSettings;
+/// use charset_normalizer_rs::from_reader;
+///
+/// let mut reader = Cursor::new("Qu'est ce que une étoile? Hello world!".as_bytes());
+/// let settings = NormalizerSettings {
+///     max_read_bytes: Some(26),
+///     ..Default::default()
+/// };
+/// let result = from_reader(&mut reader, Some(settings)).unwrap();
+/// assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+/// assert_eq!(result.get_best().unwrap().raw(), "Qu'est ce que une étoile? ".as_bytes());
+/// assert!(result.get_best().unwrap().is_truncated());
+///
+/// let mut remainder = String::new();
+/// reader.read_to_string(&mut remainder).unwrap();
+/// assert_eq!(remainder, "Hello world!");
+/// ```
+pub fn from_reader<R: Read>(
+    reader: R,
+    settings: Option<NormalizerSettings>,
+) -> Result<CharsetMatches, Error> {
+    Detector::new(settings.unwrap_or_default())?.detect_reader(reader)
+}
+
+// Encodings which can be probed concurrently, ahead of the probing loop: the following ones
+// up to the first one similar to an encoding before it (the loop skips it if that one soft
+// fails). Encodings similar to already soft failed ones are left out, the loop skips them
+// once they pass the pre-check.
+#[cfg(feature = "parallel")]
+fn probing_wave<'a>(
+    detector: &Detector,
+    encodings: &[&'a str],
+    soft_failed: &[&str],
+) -> Vec<&'a str> {
+    let mut wave: Vec<&'a str> = vec![];
+    for &encoding_iana in encodings {
+        if wave
+            .iter()
+            .any(|probed| detector.is_cp_similar(encoding_iana, probed))
+        {
+            break;
+        }
+        if !soft_failed
+            .iter()
+            .any(|failed| detector.is_cp_similar(encoding_iana, failed))
+        {
+            wave.push(encoding_iana);
+        }
+    }
+    wave
+}
+
+// Read payload within the read budget and run detection over it
+pub(crate) fn read_and_detect<R: Read>(
+    detector: &Detector,
+    reader: R,
+    size_hint: usize,
+) -> Result<CharsetMatches, Error> {
+    let read_limit = detector.settings().max_read_bytes.unwrap_or(usize::MAX);
+
+    // one byte over the budget tells if payload goes on (it is processed as well,
+    // so raw bytes of matches are exactly the consumed ones)
+    let mut buffer = Vec::with_capacity(size_hint.min(read_limit.saturating_add(1)));
+    reader
+        .take((read_limit as u64).saturating_add(1))
+        .read_to_end(&mut buffer)?;
+
+    // budget is exhausted, so the payload may be cut in the middle of a sequence
+    let is_truncated = buffer.len() > read_limit;
+    if is_truncated {
+        trace!(
+            "Read budget of {} byte(s) is exhausted, payload is processed as truncated.",
+            read_limit
+        );
+    }
+    detect_with(detector, &buffer, is_truncated).map(|(results, _)| results)
 }
@@ -0,0 +1,194 @@
+use crate::consts::HTML_ENTITIES;
+use crate::utils::find_subsequence;
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Markup stripping
+// HTML / XML tags carry no information about encoding, but dominate chunks of web pages
+/////////////////////////////////////////////////////////////////////////////////////
+
+// Content without markup, with map of its offsets to offsets of original content
+pub(crate) struct StrippedMarkup {
+    pub(crate) content: Vec<u8>,
+    // (offset in stripped content, offset in original content) of every copied run
+    runs: Vec<(usize, usize)>,
+}
+
+impl StrippedMarkup {
+    // Offset in original content for the offset in stripped one
+    pub(crate) fn original_offset(&self, offset: usize) -> usize {
+        match self
+            .runs
+            .partition_point(|&(stripped, _)| stripped <= offset)
+        {
+            0 => offset,
+            idx => self.runs[idx - 1].1 + offset - self.runs[idx - 1].0,
+        }
+    }
+
+    // Offset in stripped content for the offset in original one (offset within removed markup
+    // goes to the position where it has been removed)
+    pub(crate) fn stripped_offset(&self, offset: usize) -> usize {
+        match self
+            .runs
+            .partition_point(|&(_, original)| original <= offset)
+        {
+            0 => 0,
+            idx => {
+                let (stripped, original) = self.runs[idx - 1];
+                let run_end = self
+                    .runs
+                    .get(idx)
+                    .map_or(self.content.len(), |&(next, _)| next);
+                (stripped + offset - original).min(run_end)
+            }
+        }
+    }
+
+    fn push(&mut self, original_offset: usize, data: &[u8]) {
+        let is_continued = self.runs.last().is_some_and(|&(stripped, original)| {
+            original + self.content.len() - stripped == original_offset
+        });
+        if !is_continued {
+            self.runs.push((self.content.len(), original_offset));
+        }
+        self.content.extend_from_slice(data);
+    }
+}
+
+// Content looks like HTML / XML document: it starts with a tag and has more of them
+pub(crate) fn looks_like_markup(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(4096)];
+    let head = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+    head.iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .is_some_and(|start| markup_end(head, start).is_some())
+        && head
+            .windows(2)
+            .filter(|w| w[0] == b'<' && (w[1].is_ascii_alphabetic() || w[1] == b'/'))
+            .count()
+            >= 2
+}
+
+// Strip tags (with attributes), comments, scripts and styles, and resolve character references.
+// Every removed markup is replaced by one space (unless it is at the beginning or there is
+// a whitespace already).
+// Characters references to non-ASCII characters are dropped, unless resolve_non_ascii is set
+// (content is decoded text, so they can be written as UTF-8).
+pub(crate) fn strip_markup(bytes: &[u8], resolve_non_ascii: bool) -> StrippedMarkup {
+    let mut stripped = StrippedMarkup {
+        content: Vec::with_capacity(bytes.len()),
+        runs: vec![],
+    };
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'<' => {
+                if let Some(end) = markup_end(bytes, pos) {
+                    if stripped
+                        .content
+                        .last()
+                        .is_some_and(|b| !b.is_ascii_whitespace())
+                    {
+                        stripped.push(pos, b" ");
+                    }
+                    pos = end;
+                    continue;
+                }
+            }
+            b'&' => {
+                if let Some((end, character)) = character_reference(bytes, pos) {
+                    if let Some(ch) = character.filter(|ch| ch.is_ascii() || resolve_non_ascii) {
+                        stripped.push(pos, ch.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                    pos = end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        stripped.push(pos, &bytes[pos..=pos]);
+        pos += 1;
+    }
+    stripped
+}
+
+// Same thing than strip_markup, but for decoded text
+pub(crate) fn strip_markup_text(text: &str) -> String {
+    // markup is cut at ASCII characters only, so the rest is still valid UTF-8
+    String::from_utf8(strip_markup(text.as_bytes(), true).content).unwrap_or_default()
+}
+
+// Position after markup (tag, comment, declaration, processing instruction) which starts at pos
+fn markup_end(bytes: &[u8], pos: usize) -> Option<usize> {
+    let rest = &bytes[pos..];
+    if !rest.starts_with(b"<") {
+        return None;
+    }
+    if rest.starts_with(b"<!--") {
+        return Some(find_subsequence(bytes, pos + 4, b"-->").map_or(bytes.len(), |idx| idx + 3));
+    }
+    if rest.starts_with(b"<!") || rest.starts_with(b"<?") {
+        return find_subsequence(bytes, pos + 2, b">").map(|idx| idx + 1);
+    }
+
+    let is_closing = rest.get(1) == Some(&b'/');
+    let name_start = pos + 1 + usize::from(is_closing);
+    if !bytes.get(name_start)?.is_ascii_alphabetic() {
+        return None;
+    }
+    let name_len = bytes[name_start..]
+        .iter()
+        .position(|b| !(b.is_ascii_alphanumeric() || *b == b'-' || *b == b':'))
+        .unwrap_or(bytes.len() - name_start);
+    let name = bytes[name_start..name_start + name_len].to_ascii_lowercase();
+
+    // end of tag, attribute values may contain ">"
+    let mut quote: Option<u8> = None;
+    let mut end = name_start + name_len;
+    loop {
+        match (*bytes.get(end)?, quote) {
+            (b'>', None) => break,
+            (b @ (b'"' | b'\''), None) => quote = Some(b),
+            (b, Some(q)) if b == q => quote = None,
+            _ => {}
+        }
+        end += 1;
+    }
+
+    // content of scripts and styles is not text
+    if !is_closing && bytes[end - 1] != b'/' && (name == b"script" || name == b"style") {
+        let closing = [b"</".as_slice(), &name].concat();
+        return Some(
+            bytes[end..]
+                .windows(closing.len())
+                .position(|window| window.eq_ignore_ascii_case(&closing))
+                .and_then(|idx| find_subsequence(bytes, end + idx, b">"))
+                .map_or(bytes.len(), |idx| idx + 1),
+        );
+    }
+    Some(end + 1)
+}
+
+// Character reference (f.e. "&amp;", "&#233;", "&#xE9;") which starts at pos: position
+// after it and referenced character (None if it is unknown)
+fn character_reference(bytes: &[u8], pos: usize) -> Option<(usize, Option<char>)> {
+    let semicolon = bytes[pos..]
+        .iter()
+        .take(34)
+        .position(|&b| b == b';')
+        .map(|idx| pos + idx)?;
+    let reference = std::str::from_utf8(&bytes[pos + 1..semicolon]).ok()?;
+    let character = if let Some(hex) = reference
+        .strip_prefix("#x")
+        .or_else(|| reference.strip_prefix("#X"))
+    {
+        u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+    } else if let Some(decimal) = reference.strip_prefix('#') {
+        decimal.parse::<u32>().ok().and_then(char::from_u32)
+    } else if !reference.is_empty() && reference.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        HTML_ENTITIES.get(reference).copied()
+    } else {
+        return None;
+    };
+    Some((semicolon + 1, character))
+}
@@ -2,9 +2,10 @@ use cached::proc_macro::cached;
 use log::{log_enabled, trace};
 use ordered_float::OrderedFloat;
 
-pub(crate) mod plugins;
-pub(crate) mod structs;
+pub mod plugins;
+pub mod structs;
 
+use crate::entity::{MessDetectorCacheKey, MessDetectorSettings};
 use plugins::{
     ArchaicUpperLowerPlugin, CjkInvalidStopPlugin, MessDetectorPlugin, SuperWeirdWordPlugin,
     SuspiciousDuplicateAccentPlugin, SuspiciousRangePlugin, TooManyAccentuatedPlugin,
@@ -16,14 +17,9 @@ use structs::MessDetectorChar;
 // Mess detection module
 //
 
-// Compute a mess ratio given a decoded bytes sequence. The maximum threshold does stop the computation earlier.
-#[cached(size = 2048)]
-pub(crate) fn mess_ratio(
-    decoded_sequence: String,
-    maximum_threshold: Option<OrderedFloat<f32>>,
-) -> f32 {
-    let maximum_threshold = f32::from(maximum_threshold.unwrap_or(OrderedFloat(0.2)));
-    let mut detectors: Vec<Box<dyn MessDetectorPlugin>> = vec![
+// New instances of built-in detectors
+pub(crate) fn builtin_plugins() -> Vec<Box<dyn MessDetectorPlugin>> {
+    vec![
         Box::<TooManySymbolOrPunctuationPlugin>::default(),
         Box::<TooManyAccentuatedPlugin>::default(),
         Box::<UnprintablePlugin>::default(),
@@ -32,8 +28,83 @@ pub(crate) fn mess_ratio(
         Box::<SuperWeirdWordPlugin>::default(),
         Box::<CjkInvalidStopPlugin>::default(),
         Box::<ArchaicUpperLowerPlugin>::default(),
-    ];
+    ]
+}
+
+// Names of detectors with their (weighted) ratios, which sum up to mess ratio
+pub(crate) type MessBreakdown = Vec<(String, f32)>;
+
+// Compute a mess ratio given a decoded bytes sequence. The maximum threshold does stop the computation earlier.
+// Only built-in detectors are used (detection uses configured_mess_ratio).
+#[cfg(test)]
+pub(crate) fn mess_ratio(
+    decoded_sequence: String,
+    maximum_threshold: Option<OrderedFloat<f32>>,
+) -> f32 {
+    builtin_mess_ratio(decoded_sequence, maximum_threshold).0
+}
+
+// Mess ratio with its breakdown. Only built-in detectors are used, so the result can be cached.
+#[cached(size = 2048)]
+fn builtin_mess_ratio(
+    decoded_sequence: String,
+    maximum_threshold: Option<OrderedFloat<f32>>,
+) -> (f32, MessBreakdown) {
+    let maximum_threshold = f32::from(maximum_threshold.unwrap_or(OrderedFloat(0.2)));
+    let detectors = builtin_plugins().into_iter().map(|d| (d, 1.0)).collect();
+    compute_mess_ratio(&decoded_sequence, maximum_threshold, detectors)
+}
+
+// Compute a mess ratio (with its breakdown) with detectors configured by settings.
+// Default settings share the cache of built-in detectors, others are cached by their key.
+pub(crate) fn configured_mess_ratio(
+    decoded_sequence: String,
+    maximum_threshold: OrderedFloat<f32>,
+    settings: &MessDetectorSettings,
+) -> (f32, MessBreakdown) {
+    if settings.is_default() {
+        return builtin_mess_ratio(decoded_sequence, Some(maximum_threshold));
+    }
+    custom_mess_ratio(decoded_sequence, maximum_threshold, settings)
+}
 
+#[cached(
+    size = 2048,
+    key = "(String, OrderedFloat<f32>, MessDetectorCacheKey)",
+    convert = r#"{ (decoded_sequence.clone(), maximum_threshold, settings.cache_key()) }"#
+)]
+fn custom_mess_ratio(
+    decoded_sequence: String,
+    maximum_threshold: OrderedFloat<f32>,
+    settings: &MessDetectorSettings,
+) -> (f32, MessBreakdown) {
+    compute_mess_ratio(&decoded_sequence, *maximum_threshold, settings.detectors())
+}
+
+// Mean ratio of every detector over several sequences, which were measured by the same detectors
+pub(crate) fn mean_mess_breakdown(breakdowns: &[MessBreakdown]) -> MessBreakdown {
+    let Some(first) = breakdowns.first() else {
+        return vec![];
+    };
+    first
+        .iter()
+        .enumerate()
+        .map(|(idx, (name, _))| {
+            let sum: f32 = breakdowns.iter().map(|breakdown| breakdown[idx].1).sum();
+            (name.clone(), sum / breakdowns.len() as f32)
+        })
+        .collect()
+}
+
+// Sum of weighted ratios of detectors, which are fed with all chars of the sequence
+fn compute_mess_ratio(
+    decoded_sequence: &str,
+    maximum_threshold: f32,
+    mut detectors: Vec<(Box<dyn MessDetectorPlugin>, f32)>,
+) -> (f32, MessBreakdown) {
+    let total_ratio = |detectors: &[(Box<dyn MessDetectorPlugin>, f32)]| -> f32 {
+        detectors.iter().map(|(d, weight)| d.ratio() * weight).sum()
+    };
     let mut mean_mess_ratio: Option<f32> = None;
     let early_calc_period: usize = match decoded_sequence.chars().count() {
         ..=510 => 32,
@@ -49,18 +120,18 @@ pub(crate) fn mess_ratio(
         let mess_char = MessDetectorChar::new(ch);
         detectors
             .iter_mut()
-            .filter(|detector| detector.eligible(&mess_char))
-            .for_each(|detector| detector.feed(&mess_char));
+            .filter(|(detector, _)| detector.eligible(&mess_char))
+            .for_each(|(detector, _)| detector.feed(&mess_char));
 
         if index % early_calc_period == early_calc_period - 1 {
-            let early_mess_ratio: f32 = detectors.iter().map(|x| x.ratio()).sum();
+            let early_mess_ratio = total_ratio(&detectors);
             if early_mess_ratio >= maximum_threshold {
                 mean_mess_ratio = Some(early_mess_ratio);
                 break;
             }
         }
     }
-    let return_ratio = mean_mess_ratio.unwrap_or(detectors.iter().map(|x| x.ratio()).sum());
+    let return_ratio = mean_mess_ratio.unwrap_or_else(|| total_ratio(&detectors));
 
     if log_enabled!(log::Level::Trace) {
         trace!(
@@ -71,12 +142,16 @@ pub(crate) fn mess_ratio(
             maximum_threshold,
             detectors
             .iter()
-            .filter(|d| d.ratio() > 0.0)
-            .map(|d| format!("{} produces ratio: {}", d.name(), d.ratio()))
+            .filter(|(d, _)| d.ratio() > 0.0)
+            .map(|(d, weight)| format!("{} produces ratio: {} (weight {})", d.name(), d.ratio(), weight))
             .collect::<Vec<String>>()
             .join("===")
         );
     }
 
-    return_ratio
+    let breakdown = detectors
+        .iter()
+        .map(|(d, weight)| (d.name().to_string(), d.ratio() * weight))
+        .collect();
+    (return_ratio, breakdown)
 }
@@ -3,23 +3,61 @@ use crate::{
     utils::{is_suspiciously_successive_range, remove_accent},
 };
 
-// Base abstract trait used for mess detection plugins.
-// All detectors MUST extend and implement given methods.
-pub(super) trait MessDetectorPlugin {
-    // Name of plugin
+/// Mess detection plugin. Characters of decoded chunk are fed into plugin one by one
+/// (followed by extra `'\n'`), then its ratio is added to mess ratio of the chunk.
+/// A new plugin is created for every chunk.
+///
+/// ```rust
+/// use charset_normalizer_rs::md::plugins::MessDetectorPlugin;
+/// use charset_normalizer_rs::md::structs::{MessDetectorChar, MessDetectorCharFlags};
+///
+/// // OCR output: digits glued to letters ("he11o") are suspicious
+/// #[derive(Default)]
+/// struct DigitInWordPlugin {
+///     character_count: u64,
+///     suspicious_count: u64,
+///     previous_alphabetic: bool,
+/// }
+///
+/// impl MessDetectorPlugin for DigitInWordPlugin {
+///     fn eligible(&self, character: &MessDetectorChar) -> bool {
+///         !character.is(MessDetectorCharFlags::UNPRINTABLE)
+///     }
+///     fn feed(&mut self, character: &MessDetectorChar) {
+///         self.character_count += 1;
+///         if self.previous_alphabetic && character.is(MessDetectorCharFlags::ASCII_DIGIT) {
+///             self.suspicious_count += 1;
+///         }
+///         self.previous_alphabetic = character.is(MessDetectorCharFlags::ALPHABETIC);
+///     }
+///     fn ratio(&self) -> f32 {
+///         match self.character_count {
+///             0 => 0.0,
+///             count => self.suspicious_count as f32 / count as f32,
+///         }
+///     }
+/// }
+///
+/// assert_eq!(DigitInWordPlugin::default().name(), "DigitInWordPlugin");
+/// ```
+pub trait MessDetectorPlugin {
+    /// Name of plugin (name of its type by default)
     fn name(&self) -> &str {
-        std::any::type_name::<Self>().split("::").last().unwrap()
+        std::any::type_name::<Self>()
+            .split("::")
+            .last()
+            .unwrap_or_default()
     }
 
-    // Determine if given character should be fed in
+    /// Determine if given character should be fed in
     fn eligible(&self, character: &MessDetectorChar) -> bool;
 
-    // The main routine to be executed upon character.
-    // Insert the logic in witch the text would be considered chaotic.
+    /// The main routine to be executed upon character.
+    /// Insert the logic in witch the text would be considered chaotic.
     fn feed(&mut self, character: &MessDetectorChar);
 
-    // Compute the chaos ratio based on what your feed() has seen.
-    // Must NOT be lower than 0.; No restriction gt 0.
+    /// Compute the chaos ratio based on what your feed() has seen.
+    /// Must NOT be lower than 0.; No restriction gt 0.
     fn ratio(&self) -> f32;
 }
 
@@ -141,27 +179,22 @@ impl MessDetectorPlugin for SuspiciousDuplicateAccentPlugin {
     }
     fn feed(&mut self, character: &MessDetectorChar) {
         self.character_count += 1;
-        if self.last_latin_character.is_some()
-            && character.is(MessDetectorCharFlags::ACCENTUATED)
-            && self
-                .last_latin_character
-                .unwrap()
-                .is(MessDetectorCharFlags::ACCENTUATED)
-        {
-            if character.is(MessDetectorCharFlags::UPPERCASE)
-                && self
-                    .last_latin_character
-                    .unwrap()
-                    .is(MessDetectorCharFlags::UPPERCASE)
+        if let Some(last_latin_character) = self.last_latin_character {
+            if character.is(MessDetectorCharFlags::ACCENTUATED)
+                && last_latin_character.is(MessDetectorCharFlags::ACCENTUATED)
             {
-                self.successive_count += 1;
-            }
+                if character.is(MessDetectorCharFlags::UPPERCASE)
+                    && last_latin_character.is(MessDetectorCharFlags::UPPERCASE)
+                {
+                    self.successive_count += 1;
+                }
 
-            // Worse if its the same char duplicated with different accent.
-            if remove_accent(character.character)
-                == remove_accent(self.last_latin_character.unwrap().character)
-            {
-                self.successive_count += 1;
+                // Worse if its the same char duplicated with different accent.
+                if remove_accent(character.character)
+                    == remove_accent(last_latin_character.character)
+                {
+                    self.successive_count += 1;
+                }
             }
         }
         self.last_latin_character = Some(*character);
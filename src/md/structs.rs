@@ -6,10 +6,8 @@ use icu_properties::{maps, sets, GeneralCategory, GeneralCategoryGroup, Script};
 use crate::consts::{COMMON_SAFE_ASCII_CHARACTERS, UTF8_MAXIMAL_ALLOCATION};
 use crate::utils::{in_range, is_accentuated, unicode_range};
 
-// Mess Plugin Char representation
-// used to collect additional information about char
-// and eliminate repeated calculations
-#[derive(Copy, Clone, PartialEq)]
+/// Flags of `MessDetectorChar`, properties of character which mess detection plugins look at
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct MessDetectorCharFlags(u32);
 
 bitflags! {
@@ -41,10 +39,16 @@ bitflags! {
     }
 }
 
-#[derive(Copy, Clone)]
-pub(crate) struct MessDetectorChar {
+/// Mess Plugin Char representation
+/// used to collect additional information about char
+/// and eliminate repeated calculations
+#[derive(Copy, Clone, Debug)]
+pub struct MessDetectorChar {
+    /// Character itself
     pub character: char,
+    /// Its properties
     pub flags: MessDetectorCharFlags,
+    /// Name of its unicode range (f.e. "Basic Latin")
     pub unicode_range: Option<&'static str>,
 }
 
@@ -55,10 +59,12 @@ impl PartialEq for MessDetectorChar {
 }
 
 impl MessDetectorChar {
+    /// Representation of character (flags are computed once per character and cached)
     pub fn new(character: char) -> Self {
         new_mess_detector_character(character)
     }
 
+    /// Check if character has the flag
     pub fn is(&self, flag: MessDetectorCharFlags) -> bool {
         self.flags.contains(flag)
     }
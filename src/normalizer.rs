@@ -1,14 +1,15 @@
-use charset_normalizer_rs::entity::NormalizerSettings;
-use charset_normalizer_rs::from_path;
+use charset_normalizer_rs::entity::{CharsetMatch, CharsetMatches, Language, NormalizerSettings};
+use charset_normalizer_rs::error::Error;
+use charset_normalizer_rs::from_paths;
 use clap::Parser;
 use dialoguer::Confirm;
 use env_logger::Env;
-use ordered_float::OrderedFloat;
 use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
-use std::{fs, process};
+use std::{fs, process, thread};
 
 /////////////////////////////////////////////////////////////////////////////////////
 // Normalizer CLI application
@@ -53,61 +54,109 @@ struct CLINormalizerArgs {
     pub threshold: f32,
 }
 
-#[derive(Default, Debug, Serialize)]
-struct CLINormalizerResult {
+/// Detection outcome, serialized with the same schema as CharsetMatch: guessed encoding,
+/// its aliases and alternatives, language, alphabets, SIG/BOM presence, chaos and coherence
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum CLIDetection<'a> {
+    Found(&'a CharsetMatch),
+    Unknown {
+        encoding: Option<String>,
+        encoding_aliases: Vec<String>,
+        alternative_encodings: Vec<String>,
+        language: &'static Language,
+        alphabets: Vec<String>,
+        has_sig_or_bom: bool,
+        chaos: String,
+        coherence: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct CLINormalizerResult<'a> {
     /// Path to analysed file
     pub path: PathBuf,
-    /// Guessed encoding
-    pub encoding: Option<String>,
-    /// Possible aliases of guessed encoding
-    pub encoding_aliases: Vec<String>,
-    /// Alternative possible encodings
-    pub alternative_encodings: Vec<String>,
-    /// Most probably language
-    pub language: String,
-    /// Found alphabets
-    pub alphabets: Vec<String>,
-    /// Does it has SIG or BOM mark?
-    pub has_sig_or_bom: bool,
-    /// Chaos (mess) level
-    pub chaos: String,
-    /// Coherence (language detection) level
-    pub coherence: String,
+    /// Detection outcome
+    #[serde(flatten)]
+    pub detection: CLIDetection<'a>,
     /// Path to decoded data
     pub unicode_path: Option<PathBuf>,
     pub is_preferred: bool,
 }
 
 fn normalizer(args: &CLINormalizerArgs) -> Result<i32, String> {
-    match (args.replace, args.normalize, args.force, args.threshold) {
-        (true, false, _, _) => return Err("Use --replace in addition to --normalize only.".into()),
-        (false, _, true, _) => return Err("Use --force in addition to --replace only.".into()),
-        (_, _, _, threshold) if !(0.0..=1.0).contains(&threshold) => {
-            return Err("--threshold VALUE should be between 0.0 and 1.0.".into())
-        }
+    match (args.replace, args.normalize, args.force) {
+        (true, false, _) => return Err("Use --replace in addition to --normalize only.".into()),
+        (false, _, true) => return Err("Use --force in addition to --replace only.".into()),
         _ => {}
     }
 
     let mut results: Vec<CLINormalizerResult> = vec![];
-    let settings = NormalizerSettings {
-        threshold: OrderedFloat(args.threshold),
-        ..Default::default()
-    };
+    // encodings of every analysed file, in order of given paths (for minimal output)
+    let mut minimal_lines: Vec<String> = vec![];
+    let settings = NormalizerSettings::builder()
+        .threshold(args.threshold)
+        .and_then(|builder| builder.build())
+        .map_err(|err| err.to_string())?;
 
-    // go through the files
+    // failure on one file does not stop processing of others
+    let mut has_failures = false;
+    let mut full_paths: Vec<PathBuf> = vec![];
     for path in &args.files {
-        let full_path = &mut fs::canonicalize(path).map_err(|err| err.to_string())?;
-        let matches = from_path(full_path, Some(settings.clone()))?;
+        match fs::canonicalize(path) {
+            Ok(full_path) => full_paths.push(full_path),
+            Err(err) => {
+                eprintln!("Unable to open {:?}: {}", path, err);
+                has_failures = true;
+            }
+        }
+    }
+
+    // detect files concurrently, then go through them in the given order
+    let concurrency = thread::available_parallelism().map_or(1, |n| n.get());
+    // results are kept in order of given paths (the same path may be given more than once)
+    let mut positions: HashMap<&PathBuf, VecDeque<usize>> = HashMap::new();
+    for (index, path) in full_paths.iter().enumerate() {
+        positions.entry(path).or_default().push_back(index);
+    }
+    let mut detections: Vec<Option<Result<CharsetMatches, Error>>> =
+        full_paths.iter().map(|_| None).collect();
+    for (path, result) in from_paths(full_paths.clone(), Some(settings), concurrency) {
+        if let Some(index) = positions.get_mut(&path).and_then(|p| p.pop_front()) {
+            detections[index] = Some(result);
+        }
+    }
+    for (path, detection) in full_paths.iter().zip(&detections) {
+        let full_path = &mut path.clone();
+        let Some(detection) = detection else {
+            continue;
+        };
+        let matches = match detection {
+            Ok(matches) => matches,
+            Err(err) => {
+                eprintln!("Unable to analyse {:?}: {}", full_path, err);
+                has_failures = true;
+                continue;
+            }
+        };
         match matches.get_best() {
             None => {
                 results.push(CLINormalizerResult {
                     path: full_path.clone(),
-                    language: "Unknown".to_string(),
-                    chaos: format!("{:.1}", 1.0),
-                    coherence: format!("{:.1}", 0.0),
+                    detection: CLIDetection::Unknown {
+                        encoding: None,
+                        encoding_aliases: vec![],
+                        alternative_encodings: vec![],
+                        language: &Language::Unknown,
+                        alphabets: vec![],
+                        has_sig_or_bom: false,
+                        chaos: format!("{:.1}", 1.0),
+                        coherence: format!("{:.1}", 0.0),
+                    },
+                    unicode_path: None,
                     is_preferred: true,
-                    ..Default::default()
                 });
+                minimal_lines.push(String::from("undefined"));
                 eprintln!(
                     "Unable to identify originating encoding for {:?}. {}",
                     full_path,
@@ -120,37 +169,25 @@ fn normalizer(args: &CLINormalizerArgs) -> Result<i32, String> {
             }
             Some(best_guess) => {
                 // add main result & alternative results
+                let mut encodings: Vec<&str> = vec![];
                 for m in matches.iter() {
                     let normalize_result = CLINormalizerResult {
                         path: full_path.clone(),
-                        encoding: Some(m.encoding().to_string()),
-                        encoding_aliases: m
-                            .encoding_aliases()
-                            .iter()
-                            .map(|s| (*s).to_string())
-                            .collect(),
-                        alternative_encodings: m
-                            .suitable_encodings()
-                            .iter()
-                            .filter(|&e| e != m.encoding())
-                            .cloned()
-                            .collect(),
-                        language: format!("{}", m.most_probably_language()),
-                        alphabets: m.unicode_ranges(),
-                        has_sig_or_bom: m.bom(),
-                        chaos: format!("{:.1}", m.chaos_percents()),
-                        coherence: format!("{:.1}", m.coherence_percents()),
+                        detection: CLIDetection::Found(m),
                         unicode_path: None,
-                        is_preferred: true,
+                        is_preferred: m == best_guess,
                     };
                     if m == best_guess {
                         results.insert(0, normalize_result);
+                        encodings.insert(0, m.encoding());
                     } else if args.alternatives {
                         results.push(normalize_result);
+                        encodings.push(m.encoding());
                     } else {
                         break;
                     }
                 }
+                minimal_lines.push(encodings.join(", "));
 
                 // normalizing if need
                 if args.normalize {
@@ -200,19 +237,10 @@ fn normalizer(args: &CLINormalizerArgs) -> Result<i32, String> {
 
     // print out results
     if args.minimal {
-        for path in &args.files {
-            let full_path = fs::canonicalize(path).map_err(|err| err.to_string())?;
-            println!(
-                "{}",
-                results
-                    .iter()
-                    .filter(|r| r.path == full_path)
-                    .map(|r| r.encoding.clone().unwrap_or("undefined".to_string()))
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            );
+        for line in &minimal_lines {
+            println!("{}", line);
         }
-    } else {
+    } else if !results.is_empty() {
         println!(
             "{}",
             if results.len() > 1 {
@@ -222,7 +250,7 @@ fn normalizer(args: &CLINormalizerArgs) -> Result<i32, String> {
             }
         );
     }
-    Ok(0)
+    Ok(i32::from(has_failures))
 }
 
 pub fn main() {
@@ -1,6 +1,7 @@
 use chardetng::EncodingDetector;
 use charset_normalizer_rs::consts::CHARDET_CORRESPONDENCE;
-use charset_normalizer_rs::from_bytes;
+use charset_normalizer_rs::detector::Detector;
+use charset_normalizer_rs::entity::NormalizerSettings;
 use charset_normalizer_rs::utils::get_large_test_datasets;
 use clap::Parser;
 use encoding::label::encoding_from_whatwg_label;
@@ -91,6 +92,15 @@ fn calc_stat(results: &Vec<PerformanceResult>) -> (Duration, Duration, f32) {
     }
 }
 
+// Best guess of charset-normalizer-rs
+fn charset_normalizer_rs_detect(detector: &Detector, bytes: &[u8]) -> String {
+    if let Some(gb) = detector.detect(bytes).unwrap().get_best() {
+        gb.encoding().to_string()
+    } else {
+        String::from("None")
+    }
+}
+
 // Performance comparison
 fn performance_compare(args: &PerformanceArgs) -> i32 {
     // read datasets from /src/tests/data/largesets
@@ -114,17 +124,24 @@ fn performance_compare(args: &PerformanceArgs) -> i32 {
     /////////////////////////////////////////////////////////////////
 
     // charset-normalizer-rs
+    let detector = Detector::new(NormalizerSettings::default()).unwrap();
+    #[cfg(feature = "encoding_rs")]
+    let detector = detector.with_encoding_rs(false);
     tested_functions.insert(
         "A) charset-normalizer-rs",
-        Box::new(|bytes: &Vec<u8>| {
-            if let Some(gb) = from_bytes(bytes, None).unwrap().get_best() {
-                gb.encoding().to_string()
-            } else {
-                String::from("None")
-            }
-        }),
+        Box::new(move |bytes: &Vec<u8>| charset_normalizer_rs_detect(&detector, bytes)),
     );
 
+    // charset-normalizer-rs with encoding_rs decoding backend
+    #[cfg(feature = "encoding_rs")]
+    {
+        let detector = Detector::new(NormalizerSettings::default()).unwrap();
+        tested_functions.insert(
+            "A) charset-normalizer-rs (encoding_rs)",
+            Box::new(move |bytes: &Vec<u8>| charset_normalizer_rs_detect(&detector, bytes)),
+        );
+    }
+
     // chardet
     tested_functions.insert(
         "B) chardet",
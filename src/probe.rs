@@ -0,0 +1,373 @@
+use crate::cd::{coherence_ratio, merge_coherence_ratios};
+use crate::consts::MAX_PROCESSED_BYTES;
+use crate::detector::Detector;
+use crate::entity::{CharsetMatch, CoherenceMatches, SamplingStrategy};
+use crate::error::Error;
+use crate::markup::{strip_markup_text, StrippedMarkup};
+use crate::md::{configured_mess_ratio, mean_mess_breakdown, MessBreakdown};
+use crate::utils::{
+    blank_box_drawing, is_ebcdic_encoding, is_invalid_chunk, is_oem_code_page, sampling_offsets,
+    ResolvedCodec,
+};
+use encoding::DecoderTrap;
+use log::trace;
+use std::sync::Arc;
+
+/////////////////////////////////////////////////////////////////////////////////////
+// Prober
+// Probing of a single encoding. Nothing here depends on results of other encodings,
+// so encodings can be probed in any order (or concurrently) and replayed later.
+/////////////////////////////////////////////////////////////////////////////////////
+
+// Outcome of probing which passed the fast pre-check
+pub(crate) enum Analysis {
+    // code page does not fit the remainder of a large sequence
+    HardFailure {
+        offset: usize,
+    },
+    // code page has failed chaos probing (or does not fit one of chunks at failed_at offset)
+    SoftFailure {
+        md_ratios: Vec<f32>,
+        decoded_payload: Option<String>,
+        failed_at: Option<usize>,
+    },
+    // code page is plausible
+    Passed {
+        md_ratios: Vec<f32>,
+        charset_match: CharsetMatch,
+    },
+}
+
+pub(crate) struct Prober<'a> {
+    pub(crate) detector: &'a Detector,
+    pub(crate) bytes: Arc<[u8]>,
+    pub(crate) steps: usize,
+    pub(crate) chunk_size: usize,
+    pub(crate) sig_encoding: Option<&'a str>,
+    pub(crate) sig_len: usize,
+    pub(crate) is_too_large_sequence: bool,
+    pub(crate) is_truncated: bool,
+    pub(crate) is_markup: bool,
+    // raw bytes without markup (only for markup of large sequence, where single byte
+    // encodings are analysed on bytes), stripped once for all candidates
+    pub(crate) stripped_bytes: Option<StrippedMarkup>,
+}
+
+impl Prober<'_> {
+    // Fast pre-check: decode sequence as a whole (or its first MAX_PROCESSED_BYTES bytes
+    // for large sequences). Decoded payload is returned only if further analysis uses it.
+    pub(crate) fn precheck(&self, encoding_iana: &str) -> Result<Option<String>, Error> {
+        let bytes_length = self.bytes.len();
+        let codec = self.codec(encoding_iana)?;
+        let is_multi_byte_decoder = codec.is_multi_byte;
+        let start_idx = self.start_idx(encoding_iana);
+        let end_idx = match self.is_too_large_sequence && !is_multi_byte_decoder {
+            true => MAX_PROCESSED_BYTES,
+            false => bytes_length,
+        };
+        match codec.decode(
+            &self.bytes[start_idx..end_idx],
+            DecoderTrap::Strict,
+            self.is_too_large_sequence && !is_multi_byte_decoder,
+            self.is_truncated,
+        ) {
+            Ok(payload) => {
+                Ok((!self.is_too_large_sequence || is_multi_byte_decoder).then_some(payload))
+            }
+            Err(err) => {
+                trace!(
+                    "Code page {} does not fit given bytes sequence at ALL.",
+                    encoding_iana,
+                );
+                Err(shift_offset(err, start_idx))
+            }
+        }
+    }
+
+    // Measure mess and coherence of chunks
+    pub(crate) fn analyze(&self, encoding_iana: &str, decoded_payload: Option<String>) -> Analysis {
+        let settings = self.detector.settings();
+        let bom_or_sig_available = self.sig_encoding == Some(encoding_iana);
+        let Ok(codec) = self.codec(encoding_iana) else {
+            return Analysis::HardFailure { offset: 0 };
+        };
+        let is_multi_byte_decoder = codec.is_multi_byte;
+
+        // lets split input by chunks and try to parse them
+        let max_chunk_gave_up = 2.max(self.steps / 4);
+        let mut early_stop_count: usize = 0;
+        let mut lazy_str_failed_at: Option<usize> = None;
+        let mut md_ratios: Vec<f32> = vec![];
+
+        // detect target languages
+        let target_languages = self.detector.target_languages(encoding_iana);
+        trace!(
+            "{} should target any language(s) of {:?}",
+            encoding_iana,
+            target_languages,
+        );
+
+        // with markup mode chunks are taken from content without markup,
+        // while the match keeps the whole decoded payload
+        let mut stripped_payload: Option<String> = None;
+        let mut stripped_bytes: Option<&StrippedMarkup> = None;
+        if self.is_markup {
+            match &decoded_payload {
+                Some(payload) => {
+                    stripped_payload = Some(strip_markup_text(payload))
+                        .filter(|stripped| !stripped.trim().is_empty())
+                }
+                // markup characters have other codes in EBCDIC, its bytes are not stripped
+                None if !is_ebcdic_encoding(encoding_iana) => {
+                    stripped_bytes = self.stripped_bytes.as_ref()
+                }
+                None => {}
+            }
+        }
+        let analysed_payload = stripped_payload.as_deref().or(decoded_payload.as_deref());
+        let analysed_bytes = stripped_bytes.map_or(&self.bytes[..], |stripped| &stripped.content);
+
+        // main loop over chunks in our input
+        // we go over bytes or chars - it depends on previous code
+        let seq_len = match analysed_payload {
+            Some(payload) => payload.chars().count(),
+            None => analysed_bytes.len(),
+        };
+        let starting_offset = match (bom_or_sig_available, &decoded_payload, stripped_bytes) {
+            (true, None, Some(stripped)) => stripped.stripped_offset(self.start_idx(encoding_iana)),
+            (true, None, None) => self.start_idx(encoding_iana),
+            _ => 0,
+        };
+        let offsets = sampling_offsets(
+            settings.sampling_strategy,
+            starting_offset,
+            seq_len,
+            self.steps,
+            self.chunk_size,
+            || match analysed_payload {
+                Some(payload) => payload
+                    .chars()
+                    .enumerate()
+                    .filter(|(_, ch)| !ch.is_ascii())
+                    .map(|(pos, _)| pos)
+                    .collect(),
+                None => analysed_bytes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| !b.is_ascii())
+                    .map(|(pos, _)| pos)
+                    .collect(),
+            },
+        );
+        // with custom sampling only chunks which carry information (non-ascii) are weighted
+        let weight_informative = settings.sampling_strategy != SamplingStrategy::EvenlySpaced;
+        let mut informative_md_ratios: Vec<f32> = vec![];
+        let mut md_breakdowns: Vec<MessBreakdown> = vec![];
+        let mut informative_md_breakdowns: Vec<MessBreakdown> = vec![];
+
+        // Chunks Loop
+        // Iterate over chunks of bytes or chars
+        let mut md_chunks: Vec<String> = vec![];
+        'chunks_loop: for offset in offsets {
+            let decoded_chunk_result = match analysed_payload {
+                // Chars processing
+                Some(payload) => Ok(payload.chars().skip(offset).take(self.chunk_size).collect()),
+                // Bytes processing
+                None => codec.decode(
+                    &analysed_bytes[offset..(offset + self.chunk_size).min(seq_len)],
+                    DecoderTrap::Strict,
+                    false,
+                    false,
+                ),
+            };
+
+            if is_invalid_chunk(&decoded_chunk_result, encoding_iana) {
+                trace!(
+                    "LazyStr Loading: After MD chunk decode, code page {} \
+                    does not fit given bytes sequence at ALL. {}",
+                    encoding_iana,
+                    match &decoded_chunk_result {
+                        Ok(_) => String::from("non-ascii"),
+                        Err(message) => message.to_string(),
+                    },
+                );
+                lazy_str_failed_at = Some(match decoded_chunk_result {
+                    Err(Error::Decode {
+                        offset: chunk_offset,
+                        ..
+                    }) if decoded_payload.is_none() => match stripped_bytes {
+                        Some(stripped) => stripped.original_offset(offset + chunk_offset),
+                        None => offset + chunk_offset,
+                    },
+                    _ => self.first_non_ascii(),
+                });
+                early_stop_count = max_chunk_gave_up;
+                break 'chunks_loop;
+            }
+            let Ok(decoded_chunk) = decoded_chunk_result else {
+                break 'chunks_loop;
+            };
+
+            // MD ratios calc
+            let is_informative = !decoded_chunk.is_ascii();
+            md_chunks.push(decoded_chunk.clone());
+            let md_chunk = match is_oem_code_page(encoding_iana) {
+                true => blank_box_drawing(&decoded_chunk),
+                false => decoded_chunk,
+            };
+            let (md_ratio, md_breakdown) =
+                configured_mess_ratio(md_chunk, settings.threshold, &settings.mess_detector);
+            md_ratios.push(md_ratio);
+            if is_informative {
+                informative_md_ratios.push(md_ratio);
+                informative_md_breakdowns.push(md_breakdown.clone());
+            }
+            md_breakdowns.push(md_breakdown);
+            if md_ratio >= *settings.threshold {
+                early_stop_count += 1;
+            }
+            if early_stop_count >= max_chunk_gave_up {
+                break 'chunks_loop;
+            }
+        }
+
+        // We might want to check the remainder of sequence
+        // Only if initial MD tests passes
+        if lazy_str_failed_at.is_none() && self.is_too_large_sequence && !is_multi_byte_decoder {
+            let decoded_chunk_result = codec.decode(
+                &self.bytes[MAX_PROCESSED_BYTES..],
+                DecoderTrap::Strict,
+                false,
+                false,
+            );
+            if is_invalid_chunk(&decoded_chunk_result, encoding_iana) {
+                trace!(
+                    "LazyStr Loading: After final lookup, code page {} does not fit \
+                    given bytes sequence at ALL. {}",
+                    encoding_iana,
+                    match &decoded_chunk_result {
+                        Ok(_) => String::from("non-ascii"),
+                        Err(message) => message.to_string(),
+                    },
+                );
+                return Analysis::HardFailure {
+                    offset: match decoded_chunk_result {
+                        Err(Error::Decode { offset, .. }) => MAX_PROCESSED_BYTES + offset,
+                        _ => self.first_non_ascii(),
+                    },
+                };
+            }
+        }
+
+        // process mean mess ratio
+        let (weighted_md_ratios, weighted_md_breakdowns) =
+            match weight_informative && !informative_md_ratios.is_empty() {
+                true => (&informative_md_ratios, &informative_md_breakdowns),
+                false => (&md_ratios, &md_breakdowns),
+            };
+        let mean_mess_ratio = match weighted_md_ratios.is_empty() {
+            true => 0.0,
+            false => weighted_md_ratios.iter().sum::<f32>() / (weighted_md_ratios.len() as f32),
+        };
+
+        if mean_mess_ratio >= *settings.threshold || early_stop_count >= max_chunk_gave_up {
+            trace!(
+                "{} was excluded because of initial chaos probing. \
+                Gave up {} time(s). Computed mean chaos is {} %.",
+                encoding_iana,
+                early_stop_count,
+                mean_mess_ratio * 100.0,
+            );
+            return Analysis::SoftFailure {
+                md_ratios,
+                decoded_payload,
+                failed_at: lazy_str_failed_at,
+            };
+        }
+        trace!(
+            "{} passed initial chaos probing. Mean measured chaos is {} %",
+            encoding_iana,
+            mean_mess_ratio * 100.0,
+        );
+
+        // CD rations calc
+        // We shall skip the CD when its about ASCII
+        // Most of the time its not relevant to run "language-detection" on it.
+        let mut cd_ratios: Vec<CoherenceMatches> = vec![];
+        if encoding_iana != "ascii" {
+            cd_ratios.extend(md_chunks.iter().filter_map(|chunk| {
+                coherence_ratio(
+                    chunk.clone(),
+                    Some(settings.language_threshold),
+                    Some(target_languages.clone()),
+                )
+                .ok()
+            }));
+        }
+
+        // process cd ratios
+        let cd_ratios_merged = merge_coherence_ratios(&cd_ratios);
+        if !cd_ratios_merged.is_empty() {
+            trace!(
+                "We detected language {:?} using {}",
+                cd_ratios_merged,
+                encoding_iana
+            );
+        }
+
+        Analysis::Passed {
+            md_ratios,
+            charset_match: CharsetMatch::new(
+                self.bytes.clone(),
+                encoding_iana,
+                mean_mess_ratio,
+                bom_or_sig_available,
+                &cd_ratios_merged,
+                // keep decoded large sequences out of memory until they are asked for
+                decoded_payload.filter(|_| !self.is_too_large_sequence),
+            )
+            .with_prior(self.detector.prior(encoding_iana))
+            .with_mess_breakdown(mean_mess_breakdown(weighted_md_breakdowns))
+            .with_codec(codec),
+        }
+    }
+
+    // Whole probing of encoding: pre-check and analysis if pre-check is passed
+    #[cfg(feature = "parallel")]
+    pub(crate) fn probe(&self, encoding_iana: &str) -> Result<Analysis, Error> {
+        self.precheck(encoding_iana)
+            .map(|decoded_payload| self.analyze(encoding_iana, decoded_payload))
+    }
+
+    // Codec of probed encoding (resolved by detector)
+    fn codec(&self, encoding_iana: &str) -> Result<ResolvedCodec<'static>, Error> {
+        self.detector.codec(encoding_iana)
+    }
+
+    // Position of the first byte which does not fit ascii
+    fn first_non_ascii(&self) -> usize {
+        self.bytes
+            .iter()
+            .position(|b| !b.is_ascii())
+            .unwrap_or_default()
+    }
+
+    // Skip SIG/BOM for encoding it belongs to
+    fn start_idx(&self, encoding_iana: &str) -> usize {
+        match self.sig_encoding == Some(encoding_iana) {
+            true => self.sig_len,
+            false => 0,
+        }
+    }
+}
+
+// Make decoding error offset relative to the whole sequence
+fn shift_offset(err: Error, shift: usize) -> Error {
+    match err {
+        Error::Decode { encoding, offset } => Error::Decode {
+            encoding,
+            offset: offset + shift,
+        },
+        err => err,
+    }
+}
@@ -0,0 +1,119 @@
+use crate::codecs::supported_encodings;
+use crate::declarations::Declaration;
+use crate::entity::Language;
+
+/////////////////////////////////////////////////////////////////////////////////////
+// DetectionReport
+// Machine-readable explanation of detection process
+/////////////////////////////////////////////////////////////////////////////////////
+
+/// What happened to a single candidate encoding during detection
+#[derive(Debug, Clone, PartialEq)]
+pub enum CandidateOutcome {
+    /// Not probed because of `include_encodings` / `exclude_encodings` settings
+    Excluded,
+    /// Not probed because encoding can be identified only with SIG/BOM or specific byte patterns
+    /// (f.e. utf-16le without NUL bytes parity, utf-7 without plausible shift sequences)
+    RequiresBom,
+    /// Encoding does not fit given bytes, `offset` points to the first problematic byte
+    HardFailure { offset: usize },
+    /// Not probed because encoding is too similar to a code page which has failed already
+    SimilarToSoftFailure { similar_to: String },
+    /// Too much chaos was measured in decoded chunks
+    SoftFailure { mess_ratios: Vec<f32> },
+    /// Encoding is plausible
+    Accepted {
+        mess_ratios: Vec<f32>,
+        mean_mess_ratio: f32,
+        coherence_matches: Vec<(&'static Language, f32)>,
+    },
+    /// Not probed because detection was finished before
+    NotTested,
+}
+
+/// Why 7-bit content was (or was not) taken for utf-7 rather than ascii
+#[derive(Debug, Clone, PartialEq)]
+pub enum Utf7Verdict {
+    /// There are no shift sequences (`+...-`), so utf-7 does not differ from ascii
+    NoShiftSequence,
+    /// Shift sequence at `offset` is not valid base64 of UTF-16 (f.e. "C++" or "+1")
+    InvalidShiftSequence { offset: usize },
+    /// Shift sequences decode to text which is too messy
+    Implausible { mess_ratio: f32 },
+    /// Shift sequences decode to plausible text, so utf-7 is probed before ascii
+    Plausible {
+        shift_sequences: usize,
+        mess_ratio: f32,
+    },
+}
+
+/// Report of detection process which explains the fate of every supported encoding
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DetectionReport {
+    /// Every supported encoding with its outcome, in order of probing
+    /// (encodings which were not probed at all go last)
+    pub candidates: Vec<(&'static str, CandidateOutcome)>,
+    /// Encodings which were probed first
+    pub prioritized_encodings: Vec<String>,
+    /// Encodings hinted by caller, probed right after prioritized ones
+    pub hinted_encodings: Vec<String>,
+    /// Encoding found with SIG/BOM mark
+    pub sig_encoding: Option<String>,
+    /// Decision between ascii and utf-7 (only for 7-bit content)
+    pub utf7_verdict: Option<Utf7Verdict>,
+    /// Encoding declarations found in content (ambiguous ones are ignored)
+    pub declarations: Vec<Declaration>,
+    /// Markup was stripped from content before mess & coherence probing
+    pub is_markup: bool,
+    /// Encoding declared in content (f.e. `<meta charset="...">`)
+    pub specified_encoding: Option<String>,
+    /// Encoding which was accepted right away, stopping further probing
+    pub early_return: Option<String>,
+    /// Encodings prepared as fallbacks (failed chaos probing, but prioritized)
+    pub prepared_fallbacks: Vec<String>,
+    /// Fallback which was used, as there was no result
+    pub used_fallback: Option<String>,
+}
+
+impl DetectionReport {
+    // Outcome for given encoding
+    pub fn outcome(&self, encoding: &str) -> Option<&CandidateOutcome> {
+        self.candidates
+            .iter()
+            .find(|(enc, _)| *enc == encoding)
+            .map(|(_, outcome)| outcome)
+    }
+
+    // Encodings which were accepted as plausible
+    pub fn accepted(&self) -> Vec<&'static str> {
+        self.candidates
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, CandidateOutcome::Accepted { .. }))
+            .map(|(enc, _)| *enc)
+            .collect()
+    }
+
+    pub(crate) fn add(&mut self, encoding: &'static str, outcome: CandidateOutcome) {
+        self.candidates.push((encoding, outcome));
+    }
+
+    // Add encodings which were not probed at all
+    pub(crate) fn complete(
+        &mut self,
+        allowed_encodings: &[&'static str],
+        bom_required: &[&'static str],
+    ) {
+        for encoding in supported_encodings() {
+            if self.outcome(encoding).is_none() {
+                let outcome = if bom_required.contains(&encoding) {
+                    CandidateOutcome::RequiresBom
+                } else if allowed_encodings.contains(&encoding) {
+                    CandidateOutcome::NotTested
+                } else {
+                    CandidateOutcome::Excluded
+                };
+                self.add(encoding, outcome);
+            }
+        }
+    }
+}
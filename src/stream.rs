@@ -0,0 +1,265 @@
+use crate::consts::ENCODING_MARKS;
+use crate::detect_with;
+use crate::detector::Detector;
+use crate::entity::{CharsetMatches, NormalizerSettings};
+use crate::error::Error;
+use crate::utils::{any_specified_encoding, identify_sig_or_bom};
+use log::{debug, trace};
+
+/////////////////////////////////////////////////////////////////////////////////////
+// StreamingDetector
+// Incremental detection for payloads which come in pieces (sockets, pipes, etc.)
+/////////////////////////////////////////////////////////////////////////////////////
+
+// Longest byte sequence of a single character (utf-32, gb18030), bytes of a sequence which
+// may be cut at the end of a piece are kept until the next one
+const MAX_SEQUENCE_LEN: usize = 4;
+
+/// Charset detector which accepts bytes in pieces.
+///
+/// Pieces given to `feed` are buffered, but no more than `max_read_bytes` bytes of them
+/// (as much as `from_reader` would read). Once enough data is collected, the usual detection
+/// workflow (SIG/BOM check, declarative mark search, mess & coherence probing) is run over
+/// the buffered prefix. As soon as one candidate clearly wins (SIG/BOM, a confirmed declaration
+/// or a multi-byte encoding really used in content), probing stops, only the bytes needed for
+/// sampling (`steps * chunk_size`, but no more than `max_read_bytes`) are still buffered, and
+/// every next piece is only checked to fit the winner. If the winner does not fit a piece,
+/// pieces are buffered and probed as before the winner was found. `finish` returns matches over the buffered prefix: the winner if it fits
+/// all fed bytes, or the result of the usual detection otherwise.
+///
+/// ```rust
+/// use charset_normalizer_rs::stream::StreamingDetector;
+///
+/// let mut detector = StreamingDetector::new(None).unwrap();
+/// for piece in "Qu'est ce que une étoile?".as_bytes().chunks(4) {
+///     detector.feed(piece);
+/// }
+/// let result = detector.finish().unwrap();
+/// assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+/// ```
+pub struct StreamingDetector {
+    detector: Detector,
+    buffer: Vec<u8>,
+    budget: usize,
+    fed_len: usize,
+    next_probe_at: usize,
+    sig_checked: bool,
+    specified_encoding: Option<String>,
+    confident_encoding: Option<String>,
+    // tail of fed bytes which is not verified to fit confident encoding yet
+    pending: Vec<u8>,
+    // encodings which were confident ones, but did not fit next pieces
+    rejected_encodings: Vec<String>,
+    // results of the probe over the whole buffer, when nothing can be added to it anymore
+    final_results: Option<CharsetMatches>,
+}
+
+impl StreamingDetector {
+    // Validate settings and prepare detector
+    pub fn new(settings: Option<NormalizerSettings>) -> Result<Self, Error> {
+        let detector = Detector::new(settings.unwrap_or_default())?;
+        let settings = detector.settings();
+        let budget = settings.max_read_bytes.unwrap_or(usize::MAX);
+        let next_probe_at = settings.chunk_size.saturating_mul(settings.steps).max(1);
+        Ok(StreamingDetector {
+            detector,
+            buffer: vec![],
+            budget,
+            fed_len: 0,
+            next_probe_at,
+            sig_checked: false,
+            specified_encoding: None,
+            confident_encoding: None,
+            pending: vec![],
+            rejected_encodings: vec![],
+            final_results: None,
+        })
+    }
+
+    // Add next piece of payload
+    pub fn feed(&mut self, chunk: &[u8]) {
+        // once something was dropped, buffer is not extended anymore (it has to stay contiguous)
+        let is_closed = self.fed_len > self.buffer.len();
+        self.fed_len += chunk.len();
+
+        if self.confident_encoding.is_some() {
+            self.verify(chunk);
+            if self.confident_encoding.is_some() {
+                if !is_closed {
+                    let wanted = self
+                        .sample_size()
+                        .min(self.budget)
+                        .saturating_sub(self.buffer.len());
+                    self.buffer
+                        .extend_from_slice(&chunk[..wanted.min(chunk.len())]);
+                }
+                return;
+            }
+            // winner was rejected, so the piece is buffered as usual
+        }
+        if is_closed {
+            return;
+        }
+        let taken = (self.budget - self.buffer.len()).min(chunk.len());
+        self.buffer.extend_from_slice(&chunk[..taken]);
+
+        // check bom & sig as soon as longest mark could be fitted
+        let longest_sig_or_bom = ENCODING_MARKS.values().map(|m| m.len()).max();
+        if !self.sig_checked && Some(self.buffer.len()) >= longest_sig_or_bom {
+            self.sig_checked = true;
+            if let (Some(sig_encoding), _) = identify_sig_or_bom(&self.buffer) {
+                trace!(
+                    "Streaming detection: SIG or BOM mark found for {}.",
+                    sig_encoding
+                );
+                self.become_confident(sig_encoding, &chunk[taken..]);
+                return;
+            }
+        }
+
+        // search for declarative mark & probe content periodically, and for the last time
+        // when the budget is exhausted
+        let is_full = self.buffer.len() == self.budget;
+        if self.buffer.len() >= self.next_probe_at || (is_full && taken > 0) {
+            self.next_probe_at = self.buffer.len().saturating_mul(2);
+            self.probe(is_full, &chunk[taken..]);
+        }
+    }
+
+    // Whether one candidate has already clearly won
+    pub fn is_confident(&self) -> bool {
+        self.confident_encoding.is_some()
+    }
+
+    // Total amount of fed bytes
+    pub fn len(&self) -> usize {
+        self.fed_len
+    }
+
+    // Nothing was fed yet?
+    pub fn is_empty(&self) -> bool {
+        self.fed_len == 0
+    }
+
+    // Finalize detection over the buffered prefix of payload
+    pub fn finish(mut self) -> Result<CharsetMatches, Error> {
+        let is_truncated = self.fed_len > self.buffer.len();
+        if let Some(encoding) = self.confident_encoding.take() {
+            if self.pending.is_empty() {
+                let settings = NormalizerSettings {
+                    include_encodings: vec![encoding.clone()],
+                    exclude_encodings: vec![],
+                    ..self.detector.settings().clone()
+                };
+                let detector = Detector::new(settings)?;
+                let (results, _) = detect_with(&detector, &self.buffer, is_truncated)?;
+                if !results.is_empty() {
+                    return Ok(results);
+                }
+            }
+            debug!(
+                "Streaming detection: {} does not fit complete payload, fallback to full detection.",
+                encoding
+            );
+            self.rejected_encodings.push(encoding);
+        }
+        if self.rejected_encodings.is_empty() {
+            if let Some(results) = self.final_results.filter(|_| is_truncated) {
+                return Ok(results);
+            }
+            return detect_with(&self.detector, &self.buffer, is_truncated)
+                .map(|(results, _)| results);
+        }
+
+        let mut settings = self.detector.settings().clone();
+        settings
+            .exclude_encodings
+            .extend(self.rejected_encodings.iter().cloned());
+        let detector = Detector::new(settings)?;
+        detect_with(&detector, &self.buffer, is_truncated).map(|(results, _)| results)
+    }
+
+    // Amount of bytes sampled by detection
+    fn sample_size(&self) -> usize {
+        let settings = self.detector.settings();
+        settings.chunk_size.saturating_mul(settings.steps)
+    }
+
+    // Run detection over buffered prefix and check if there is a clear winner.
+    // Rest is the part of the last piece which did not fit into the buffer.
+    fn probe(&mut self, is_full: bool, rest: &[u8]) {
+        let settings = self.detector.settings();
+        if settings.preemptive_behaviour && self.specified_encoding.is_none() {
+            self.specified_encoding = any_specified_encoding(&self.buffer, 4096);
+        }
+
+        let Ok((results, _)) = detect_with(&self.detector, &self.buffer, true) else {
+            return;
+        };
+        if results.len() == 1 {
+            if let Some(best_guess) = results.get_best() {
+                let encoding = best_guess.encoding();
+                let is_rejected = self.rejected_encodings.iter().any(|e| e == encoding);
+                if !is_rejected
+                    && (best_guess.bom()
+                        || self.specified_encoding.as_deref() == Some(encoding)
+                        || (self.detector.codec(encoding).is_ok_and(|c| c.is_multi_byte)
+                            && best_guess.multi_byte_usage() > 0.0))
+                {
+                    trace!(
+                        "Streaming detection: {} is most likely the one after {} byte(s).",
+                        encoding,
+                        self.buffer.len()
+                    );
+                    self.become_confident(encoding.to_string(), rest);
+                    return;
+                }
+            }
+        }
+        // nothing more can be buffered, so finish over truncated payload would get the same results
+        if is_full {
+            self.final_results = Some(results);
+        }
+    }
+
+    // Stop probing, all fed bytes (the buffer and the rest of the last piece) have to fit the winner
+    fn become_confident(&mut self, encoding: String, rest: &[u8]) {
+        self.confident_encoding = Some(encoding);
+        let buffer = std::mem::take(&mut self.buffer);
+        self.verify(&buffer);
+        self.buffer = buffer;
+        self.verify(rest);
+    }
+
+    // Check that fed bytes fit confident encoding. A sequence which may be cut
+    // at the end of piece is verified together with the next piece. Shift sequence
+    // of utf-7 has no length limit, so it is kept until it is closed.
+    fn verify(&mut self, chunk: &[u8]) {
+        let Some(encoding) = self.confident_encoding.clone() else {
+            return;
+        };
+        let max_pending = match encoding.as_str() {
+            "utf-7" => usize::MAX,
+            _ => MAX_SEQUENCE_LEN,
+        };
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(chunk);
+        let prefix_len = self
+            .detector
+            .codec(&encoding)
+            .and_then(|codec| codec.decodable_prefix_len(&bytes));
+        match prefix_len {
+            Ok(len) if bytes.len() - len < max_pending => {
+                self.pending = bytes[len..].to_vec();
+            }
+            _ => {
+                debug!(
+                    "Streaming detection: {} does not fit fed bytes, it is not a clear winner anymore.",
+                    encoding
+                );
+                self.confident_encoding = None;
+                self.rejected_encodings.push(encoding);
+            }
+        }
+    }
+}
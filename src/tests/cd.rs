@@ -42,6 +42,11 @@ fn test_encoding_languages() {
         ("utf-8", Language::Unknown),
         ("windows-1251", Language::Russian),
         ("windows-1255", Language::Hebrew),
+        ("ibm437", Language::English),
+        ("ibm850", Language::Portuguese),
+        ("ibm737", Language::Greek),
+        ("ibm852", Language::Polish),
+        ("ibm865", Language::Norwegian),
     ];
     for (input, lang) in tests {
         let languages = encoding_languages(input.to_string());
@@ -59,10 +64,9 @@ fn test_alphabet_languages() {
     for (input, ignore_non_latin, expected) in tests {
         let characters: Vec<char> = input.chars().collect();
         let languages = alphabet_languages(&characters, ignore_non_latin);
-        if expected.is_none() {
-            assert_eq!(languages.len(), 0);
-        } else {
-            assert!(languages.contains(&&expected.unwrap()));
+        match expected {
+            None => assert_eq!(languages.len(), 0),
+            Some(expected) => assert!(languages.contains(&&expected)),
         }
     }
 }
@@ -162,8 +166,8 @@ fn test_merge_coherence_ratios() {
         },
     ];
     let mut output = merge_coherence_ratios(&input);
-    output.sort_unstable_by(|a, b| a.score.cmp(&b.score));
-    expected_output.sort_unstable_by(|a, b| a.score.cmp(&b.score));
+    output.sort_unstable_by_key(|a| a.score);
+    expected_output.sort_unstable_by_key(|a| a.score);
     assert_eq!(output, expected_output);
 }
 
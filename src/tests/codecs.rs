@@ -0,0 +1,259 @@
+use crate::codecs::{builtin_encodings, codec_from_label};
+use crate::consts::IANA_SUPPORTED_ALIASES;
+use crate::error::Error;
+use crate::utils::{decode, encode, iana_name};
+use encoding::{DecoderTrap, EncoderTrap};
+
+#[test]
+fn test_codec_from_label() {
+    let tests = [
+        ("UTF-32", Some("utf-32be")),
+        ("ucs-4le", Some("utf-32le")),
+        ("ucs-4be", Some("utf-32be")),
+        (" utf-32le ", Some("utf-32le")),
+        ("latin1", Some("windows-1252")),
+        ("UNICODE-1-1-UTF-7", Some("utf-7")),
+        ("Java-Modified-UTF-8", Some("mutf-8")),
+        ("utf-64", None),
+    ];
+    for (label, expected) in tests {
+        assert_eq!(
+            codec_from_label(label).map(|c| c.name()),
+            expected,
+            "{}",
+            label
+        );
+    }
+    assert_eq!(iana_name("ucs-4"), Some("utf-32be"));
+    for encoding in builtin_encodings() {
+        assert!(
+            IANA_SUPPORTED_ALIASES
+                .get(encoding)
+                .is_some_and(|aliases| aliases.contains(&encoding)),
+            "{}",
+            encoding
+        );
+    }
+}
+
+#[test]
+fn test_utf32_round_trip() {
+    let text = "Пока мы молоды 😀";
+    for (encoding, first_unit) in [
+        ("utf-32le", b"\x1f\x04\x00\x00"),
+        ("utf-32be", b"\x00\x00\x04\x1f"),
+    ] {
+        let bytes = encode(text, encoding, EncoderTrap::Strict).unwrap();
+        assert_eq!(bytes.len(), text.chars().count() * 4);
+        assert_eq!(&bytes[..4], first_unit);
+        let decoded = decode(&bytes, encoding, DecoderTrap::Strict, false, false).unwrap();
+        assert_eq!(decoded, text);
+    }
+}
+
+#[test]
+fn test_utf32_decode_errors() {
+    let tests = [
+        // code point above U+10FFFF
+        (b"a\x00\x00\x00\x00\x00\x11\x00".as_slice(), 4),
+        // surrogate
+        (b"a\x00\x00\x00\x00\xd8\x00\x00".as_slice(), 4),
+        // incomplete unit
+        (b"a\x00\x00\x00b\x00".as_slice(), 4),
+    ];
+    for (bytes, expected_offset) in tests {
+        match decode(bytes, "utf-32le", DecoderTrap::Strict, false, false) {
+            Err(Error::Decode { offset, .. }) => assert_eq!(offset, expected_offset),
+            other => panic!("unexpected result {:?} for {:?}", other, bytes),
+        }
+    }
+
+    // truncated unit at the end of chunk is dropped
+    let bytes = encode("abc", "utf-32le", EncoderTrap::Strict).unwrap();
+    let decoded = decode(&bytes[..10], "utf-32le", DecoderTrap::Strict, false, true).unwrap();
+    assert_eq!(decoded, "ab");
+}
+
+#[test]
+fn test_utf7_round_trip() {
+    let tests = [
+        ("Hi Mom -☺-!", "Hi Mom -+Jjo--!"),
+        ("日本語", "+ZeVnLIqe-"),
+        ("1 + 1 = 2", "1 +- 1 = 2"),
+        ("Caf\u{e9} \u{1F600}", "Caf+AOk- +2D3eAA-"),
+    ];
+    for (text, expected) in tests {
+        let bytes = encode(text, "utf-7", EncoderTrap::Strict).unwrap();
+        assert_eq!(String::from_utf8_lossy(&bytes), expected);
+        let decoded = decode(&bytes, "utf-7", DecoderTrap::Strict, false, false).unwrap();
+        assert_eq!(decoded, text);
+    }
+
+    // shift sequence may be terminated by any non-base64 character
+    let decoded = decode(b"+ZeVnLIqe.", "utf-7", DecoderTrap::Strict, false, false).unwrap();
+    assert_eq!(decoded, "日本語.");
+}
+
+#[test]
+fn test_utf7_decode_errors() {
+    let tests = [
+        // non-zero padding bits
+        (b"1+1=2".as_slice(), 1),
+        // "+" is not followed by base64
+        (b"a + b".as_slice(), 2),
+        // unpaired surrogate
+        (b"a+2D0-".as_slice(), 1),
+        // 8-bit byte
+        (b"caf\xe9".as_slice(), 3),
+    ];
+    for (bytes, expected_offset) in tests {
+        match decode(bytes, "utf-7", DecoderTrap::Strict, false, false) {
+            Err(Error::Decode { offset, .. }) => assert_eq!(offset, expected_offset),
+            other => panic!("unexpected result {:?} for {:?}", other, bytes),
+        }
+    }
+
+    // characters of invalid shift sequence are replaced altogether
+    let decoded = decode(b"a+AGLcAA-c", "utf-7", DecoderTrap::Replace, false, false).unwrap();
+    assert_eq!(decoded, "a\u{fffd}c");
+}
+
+#[test]
+fn test_utf8_variants_round_trip() {
+    let tests = [
+        (
+            "cesu-8",
+            "a\u{0}é😀",
+            b"a\x00\xc3\xa9\xed\xa0\xbd\xed\xb8\x80".as_slice(),
+        ),
+        (
+            "mutf-8",
+            "a\u{0}é😀",
+            b"a\xc0\x80\xc3\xa9\xed\xa0\xbd\xed\xb8\x80".as_slice(),
+        ),
+        (
+            "wtf-8",
+            "a\u{0}é😀",
+            b"a\x00\xc3\xa9\xf0\x9f\x98\x80".as_slice(),
+        ),
+    ];
+    for (encoding, text, expected) in tests {
+        let bytes = encode(text, encoding, EncoderTrap::Strict).unwrap();
+        assert_eq!(bytes, expected, "{}", encoding);
+        let decoded = decode(&bytes, encoding, DecoderTrap::Strict, false, false).unwrap();
+        assert_eq!(decoded, text, "{}", encoding);
+    }
+
+    // lone surrogates of wtf-8 are replaced
+    let decoded = decode(
+        b"a\xed\xa0\x80b\xed\xb0\x80",
+        "wtf-8",
+        DecoderTrap::Strict,
+        false,
+        false,
+    )
+    .unwrap();
+    assert_eq!(decoded, "a\u{FFFD}b\u{FFFD}");
+}
+
+#[test]
+fn test_utf8_variants_decode_errors() {
+    let tests = [
+        // lone surrogate
+        ("cesu-8", b"ab\xed\xa0\x80c".as_slice(), 2),
+        // 4-bytes sequence
+        ("cesu-8", b"ab\xf0\x9f\x98\x80".as_slice(), 2),
+        // overlong NUL
+        ("cesu-8", b"ab\xc0\x80".as_slice(), 2),
+        // raw NUL
+        ("mutf-8", b"ab\x00".as_slice(), 2),
+        // paired surrogates must be a 4-bytes sequence
+        ("wtf-8", b"ab\xed\xa0\xbd\xed\xb8\x80".as_slice(), 2),
+        // truncated pair
+        ("mutf-8", b"ab\xed\xa0\xbd\xed".as_slice(), 2),
+    ];
+    for (encoding, bytes, expected_offset) in tests {
+        match decode(bytes, encoding, DecoderTrap::Strict, false, false) {
+            Err(Error::Decode { offset, .. }) => assert_eq!(offset, expected_offset),
+            other => panic!("unexpected result {:?} for {} {:?}", other, encoding, bytes),
+        }
+    }
+}
+
+#[test]
+fn test_oem_code_pages() {
+    let tests = [
+        (
+            "ibm437",
+            "╔═╗ Größe: ½ ▒▓█",
+            b"\xc9\xcd\xbb Gr\x94\xe1e: \xab \xb1\xb2\xdb".as_slice(),
+        ),
+        (
+            "ibm737",
+            "Καλημέρα",
+            b"\x89\x98\xa2\x9e\xa3\xe2\xa8\x98".as_slice(),
+        ),
+        ("ibm775", "Ąžuolas", b"\xb5\xd8uolas".as_slice()),
+        ("ibm850", "Àçã ©", b"\xb7\x87\xc6 \xb8".as_slice()),
+        ("ibm852", "Łódź", b"\x9d\xa2d\xab".as_slice()),
+        ("ibm865", "Blåbær ¤", b"Bl\x86b\x91r \xaf".as_slice()),
+    ];
+    for (encoding, text, bytes) in tests {
+        assert_eq!(
+            decode(bytes, encoding, DecoderTrap::Strict, false, false).unwrap(),
+            text,
+            "{}",
+            encoding
+        );
+        assert_eq!(encode(text, encoding, EncoderTrap::Strict).unwrap(), bytes);
+    }
+    assert_eq!(iana_name("CP437"), Some("ibm437"));
+    assert!(encode("€", "ibm437", EncoderTrap::Strict).is_err());
+}
+
+#[test]
+fn test_ebcdic_code_pages() {
+    let tests = [
+        (
+            "ibm037",
+            "Hello, World! [x]",
+            b"\xc8\x85\x93\x93\x96\x6b\x40\xe6\x96\x99\x93\x84\x5a\x40\xba\xa7\xbb".as_slice(),
+        ),
+        (
+            "ibm500",
+            "Hello, World! [x]",
+            b"\xc8\x85\x93\x93\x96\x6b\x40\xe6\x96\x99\x93\x84\x4f\x40\x4a\xa7\x5a".as_slice(),
+        ),
+        ("ibm1047", "a[0]^b", b"\x81\xad\xf0\xbd\x5f\x82".as_slice()),
+        (
+            "ibm01140",
+            "Preis: 5€",
+            b"\xd7\x99\x85\x89\xa2\x7a\x40\xf5\x9f".as_slice(),
+        ),
+    ];
+    for (encoding, text, bytes) in tests {
+        assert_eq!(
+            decode(bytes, encoding, DecoderTrap::Strict, false, false).unwrap(),
+            text,
+            "{}",
+            encoding
+        );
+        assert_eq!(encode(text, encoding, EncoderTrap::Strict).unwrap(), bytes);
+    }
+
+    // both NEL and LF are decoded as LF, which is encoded back as LF
+    let decoded = decode(
+        b"\xc1\x15\xc2\x25",
+        "ibm037",
+        DecoderTrap::Strict,
+        false,
+        false,
+    );
+    assert_eq!(decoded.unwrap(), "A\nB\n");
+    assert_eq!(
+        encode("A\n", "ibm037", EncoderTrap::Strict).unwrap(),
+        b"\xc1\x25"
+    );
+    assert_eq!(iana_name("EBCDIC-CP-US"), Some("ibm037"));
+    assert!(encode("€", "ibm037", EncoderTrap::Strict).is_err());
+}
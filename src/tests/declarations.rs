@@ -0,0 +1,193 @@
+use crate::declarations::{find_declarations, DeclarationSyntax};
+use crate::from_bytes_with_report;
+use crate::utils::any_specified_encoding;
+
+#[test]
+fn test_find_declarations() {
+    let tests = [
+        (
+            b"<html><head><meta charset=\"utf8\"></head>".as_slice(),
+            DeclarationSyntax::HtmlMetaCharset,
+            "<meta charset=\"utf8\"",
+            Some("utf-8"),
+            true,
+        ),
+        (
+            b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\">",
+            DeclarationSyntax::HtmlHttpEquiv,
+            "<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\"",
+            Some("windows-1251"),
+            true,
+        ),
+        (
+            b"<?xml version=\"1.0\" encoding=\"ISO-8859-2\"?><a/>",
+            DeclarationSyntax::XmlDeclaration,
+            "<?xml version=\"1.0\" encoding=\"ISO-8859-2\"",
+            Some("iso-8859-2"),
+            true,
+        ),
+        (
+            b"<a>text</a>\n<?xml version=\"1.0\" encoding=\"ISO-8859-2\"?>",
+            DeclarationSyntax::XmlDeclaration,
+            "<?xml version=\"1.0\" encoding=\"ISO-8859-2\"",
+            Some("iso-8859-2"),
+            false,
+        ),
+        (
+            b"\xef\xbb\xbf@charset \"UTF-8\";\nbody {}",
+            DeclarationSyntax::CssCharset,
+            "@charset \"UTF-8\";",
+            Some("utf-8"),
+            true,
+        ),
+        (
+            b"#!/usr/bin/env python\n# vim: set fileencoding=koi8_r :\n",
+            DeclarationSyntax::VimModeline,
+            "vim: set fileencoding=koi8_r",
+            Some("koi8-r"),
+            true,
+        ),
+        (
+            b"#!/usr/bin/env python\n# -*- coding: latin-1 -*-\n",
+            DeclarationSyntax::EmacsModeline,
+            "-*- coding: latin-1 -*-",
+            Some("windows-1252"),
+            true,
+        ),
+        (
+            b"# coding=cp1251\nimport os\n",
+            DeclarationSyntax::Pep263,
+            "# coding=cp1251",
+            Some("windows-1251"),
+            true,
+        ),
+        (
+            b"import os\n\n\n# coding=cp1251\n",
+            DeclarationSyntax::Pep263,
+            "# coding=cp1251",
+            Some("windows-1251"),
+            false,
+        ),
+        (
+            b"SET NAMES utf8mb4;\nINSERT INTO t VALUES (1);",
+            DeclarationSyntax::SqlSetNames,
+            "SET NAMES utf8mb4",
+            Some("utf-8"),
+            true,
+        ),
+        (
+            b"-- dump\nset names 'cp1251';",
+            DeclarationSyntax::SqlSetNames,
+            "set names 'cp1251'",
+            Some("windows-1251"),
+            true,
+        ),
+        (
+            b"From: a@b.c\r\nContent-Type: text/plain; charset=\"koi8-r\"\r\n\r\nbody",
+            DeclarationSyntax::MimeContentType,
+            "Content-Type: text/plain; charset=\"koi8-r",
+            Some("koi8-r"),
+            true,
+        ),
+        (
+            b"From: a@b.c\r\n\r\n--b\r\nContent-Type: text/plain; charset=koi8-r\r\n",
+            DeclarationSyntax::MimeContentType,
+            "Content-Type: text/plain; charset=koi8-r",
+            Some("koi8-r"),
+            false,
+        ),
+        (
+            b"<meta charset=\"utf-57\">",
+            DeclarationSyntax::HtmlMetaCharset,
+            "<meta charset=\"utf-57\"",
+            None,
+            true,
+        ),
+        (
+            b"<meta charset=\"EUC-KR\" />",
+            DeclarationSyntax::HtmlMetaCharset,
+            "<meta charset=\"EUC-KR\" /",
+            Some("euc-kr"),
+            true,
+        ),
+        (
+            b"// vim:ft=c:fenc=latin2\n",
+            DeclarationSyntax::VimModeline,
+            "vim:ft=c:fenc=latin2",
+            Some("iso-8859-2"),
+            true,
+        ),
+    ];
+    for (sequence, syntax, declaration, encoding, unambiguous) in tests {
+        let declarations = find_declarations(sequence, 4096);
+        assert_eq!(declarations.len(), 1, "{:?}", declarations);
+        let found = &declarations[0];
+        assert_eq!(found.syntax, syntax);
+        assert_eq!(&sequence[found.span.clone()], declaration.as_bytes());
+        assert_eq!(found.encoding, encoding);
+        assert_eq!(found.unambiguous, unambiguous, "{:?}", found);
+    }
+
+    // nothing looks like a declaration
+    for sequence in [
+        b"Content-Transfer-Encoding: base64\r\n".as_slice(),
+        b"The encoding: utf-8 is the best one",
+        b"body { font-family: serif; }",
+    ] {
+        assert!(find_declarations(sequence, 4096).is_empty());
+    }
+
+    // declarations out of search zone are not found
+    assert!(find_declarations(b"          <meta charset=\"utf-8\">", 10).is_empty());
+
+    // commented out meta tags are skipped (unterminated comment lasts till the end)
+    let sequence =
+        b"<!-- <meta charset=\"koi8-r\"> --><meta charset=\"utf-8\"><!-- <meta charset=\"cp1251\">";
+    let declarations = find_declarations(sequence, 4096);
+    assert_eq!(declarations.len(), 1, "{:?}", declarations);
+    assert_eq!(declarations[0].encoding, Some("utf-8"));
+
+    // SET NAMES is unambiguous only if it is the single one
+    let declarations = find_declarations(b"SET NAMES latin1;\nSELECT 1;\nSET NAMES utf8;\n", 4096);
+    assert_eq!(declarations.len(), 2);
+    assert!(declarations.iter().all(|d| !d.unambiguous));
+}
+
+#[test]
+fn test_unambiguous_declarations() {
+    let tests = [
+        // ambiguous ones are ignored
+        (b"import os\n\n\n# coding=cp1251\n".as_slice(), None),
+        // all unambiguous declarations should agree
+        (
+            b"<?xml version=\"1.0\" encoding=\"utf-8\"?><meta charset=\"euc-kr\">",
+            None,
+        ),
+        (
+            b"<?xml version=\"1.0\" encoding=\"utf-8\"?><meta charset=\"utf8\">",
+            Some("utf-8"),
+        ),
+        (b"# coding: latin-1\n# coding=cp1251\n", None),
+        (b"<!-- <meta charset=\"euc-kr\"> -->", None),
+        (b"SET NAMES utf8;\nSET NAMES utf8;\n", None),
+    ];
+    for (sequence, expected) in tests {
+        assert_eq!(
+            any_specified_encoding(sequence, 4096).as_deref(),
+            expected,
+            "{}",
+            String::from_utf8_lossy(sequence)
+        );
+    }
+
+    // detection reports declarations and trusts unambiguous ones only
+    let mut content = b"<html><p>\xd0\xf3\xf1\xf1\xea\xe8\xe9 \xf2\xe5\xea\xf1\xf2</p>\n".to_vec();
+    content.extend(b"<?xml version=\"1.0\" encoding=\"euc-kr\"?></html>");
+    let (_, report) = from_bytes_with_report(&content, None).unwrap();
+    assert_eq!(report.declarations.len(), 1);
+    assert!(!report.declarations[0].unambiguous);
+    assert_eq!(report.specified_encoding, None);
+    assert!(!report
+        .prioritized_encodings
+        .contains(&String::from("euc-kr")));
+}
@@ -1,7 +1,12 @@
-use crate::entity::NormalizerSettings;
-use crate::from_bytes;
+use crate::consts::MAX_PROCESSED_BYTES;
+use crate::entity::{Language, NormalizerSettings, Utf8Variant};
+use crate::error::Error;
 use crate::utils::encode;
+use crate::{from_bytes, from_path, from_reader};
 use encoding::EncoderTrap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
 
 #[test]
 fn test_empty() {
@@ -94,6 +99,24 @@ fn test_content_with_bom_or_sig() {
             .unwrap(),
             "utf-8",
         ),
+        (
+            encode(
+                "\u{FEFF}我没有埋怨，磋砣的只是一些时间。",
+                "utf-32le",
+                EncoderTrap::Ignore,
+            )
+            .unwrap(),
+            "utf-32le",
+        ),
+        (
+            encode(
+                "\u{FEFF}我没有埋怨，磋砣的只是一些时间。",
+                "utf-32be",
+                EncoderTrap::Ignore,
+            )
+            .unwrap(),
+            "utf-32be",
+        ),
     ];
 
     for (input, expected_encoding) in tests {
@@ -118,6 +141,111 @@ fn test_content_with_bom_or_sig() {
     }
 }
 
+#[test]
+fn test_content_unicode_without_bom() {
+    let text = "Пока мы молоды, мы должны научиться многому. \
+        Bonjour, voici le résumé de la réunion d'hier, merci de le relire.";
+    for encoding in ["utf-16le", "utf-16be", "utf-32le", "utf-32be"] {
+        let input = encode(text, encoding, EncoderTrap::Strict).unwrap();
+        let result = from_bytes(&input, None).unwrap();
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert!(!best_guess.bom());
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+    }
+
+    // binary content does not pass for utf-16 or utf-32
+    let input = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x01\x00\x00\x00\x01\x00\x08\x06\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+    let result = from_bytes(input, None).unwrap();
+    assert!(result
+        .iter()
+        .all(|m| !m.encoding().starts_with("utf-16") && !m.encoding().starts_with("utf-32")));
+}
+
+#[test]
+fn test_content_utf8_variants() {
+    let text = "Emoji in a Java string: 😀, with accents: café, naïve.";
+    let tests = [
+        ("cesu-8", Utf8Variant::Cesu8),
+        ("mutf-8", Utf8Variant::Modified),
+    ];
+    for (encoding, expected_variant) in tests {
+        let mut input = encode(text, encoding, EncoderTrap::Strict).unwrap();
+        if encoding == "mutf-8" {
+            input.extend(b" \xc0\x80");
+        }
+        let result = from_bytes(&input, None).unwrap();
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.utf8_variant(), Some(expected_variant));
+        assert!(best_guess.decoded_payload().unwrap().starts_with(text));
+    }
+
+    let input =
+        b"C:\\Users\\file_\xed\xa0\x80.txt is a file name with lone surrogate, caf\xc3\xa9.";
+    let result = from_bytes(input, None).unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.utf8_variant(), Some(Utf8Variant::Wtf8));
+    assert!(best_guess
+        .decoded_payload()
+        .unwrap()
+        .contains("file_\u{FFFD}.txt"));
+
+    // standard utf-8 is not affected
+    let result = from_bytes(text.as_bytes(), None).unwrap();
+    assert_eq!(
+        result.get_best().unwrap().utf8_variant(),
+        Some(Utf8Variant::Standard)
+    );
+}
+
+#[test]
+fn test_content_oem_code_page() {
+    let text = "░▒▓█ SYSOP BBS ██▓▒░\n\
+        ┌────────┬───────────────────┐\n\
+        │ Datei  │ Größe der Archive │\n\
+        ├────────┼───────────────────┤\n\
+        │ SPIEL  │ 120 KB, ältere    │\n\
+        │ MUSIK  │ 340 KB, schöne    │\n\
+        └────────┴───────────────────┘\n";
+    let input = encode(text, "ibm437", EncoderTrap::Strict).unwrap();
+    let result = from_bytes(&input, None).unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert!(
+        ["ibm437", "ibm850", "ibm865"].contains(&best_guess.encoding()),
+        "{}",
+        best_guess.encoding()
+    );
+    assert!(best_guess.languages().contains(&&Language::German));
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+}
+
+#[test]
+fn test_content_ebcdic() {
+    let text = "CUSTOMER MASTER FILE EXTRACT - RUN DATE 2023-04-17\n\
+        ACCOUNT   NAME                 BALANCE   STATUS\n\
+        0001234   JOHN SMITH           1,234.56  ACTIVE\n\
+        0001235   MARY O'BRIEN         99.00     CLOSED\n\
+        Total records processed: 2; errors: none.\n";
+    let with_lf = encode(text, "ibm037", EncoderTrap::Strict).unwrap();
+    // datasets separate records by NEL
+    let with_nel: Vec<u8> = with_lf
+        .iter()
+        .map(|&b| if b == 0x25 { 0x15 } else { b })
+        .collect();
+    for input in [with_lf, with_nel] {
+        let result = from_bytes(&input, None).unwrap();
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), "ibm037");
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+        assert!(best_guess.languages().contains(&&Language::English));
+    }
+
+    // ascii content does not go to EBCDIC
+    let result = from_bytes(text.as_bytes(), None).unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "ascii");
+}
+
 #[test]
 fn test_obviously_ascii_content() {
     let tests = [
@@ -197,3 +325,107 @@ fn test_mb_cutting_chk() {
     assert_eq!(result.len(), 1);
     assert_eq!(best_guess.encoding(), "euc-kr");
 }
+
+#[test]
+fn test_from_reader_with_read_budget() {
+    let payload = "Bсеки човек има право на образование. ".repeat(64);
+    // budget cuts payload in the middle of a multibyte sequence
+    let budget = 1001;
+    let mut reader = Cursor::new(payload.as_bytes());
+    let settings = NormalizerSettings {
+        max_read_bytes: Some(budget),
+        ..Default::default()
+    };
+    let result = from_reader(&mut reader, Some(settings)).unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+    // one more byte is consumed to find out that payload goes on
+    assert_eq!(best_guess.raw(), &payload.as_bytes()[..budget + 1]);
+    assert!(result.iter().all(|m| m.is_truncated()));
+
+    // the rest of payload should stay in reader
+    let mut remainder = vec![];
+    reader.read_to_end(&mut remainder).unwrap();
+    assert_eq!(remainder, &payload.as_bytes()[budget + 1..]);
+
+    // payload of exactly budget size is not truncated, so incomplete sequence at its end counts
+    let payload = [payload.as_bytes(), b"\xd0"].concat();
+    for (budget, is_truncated) in [(payload.len(), false), (payload.len() - 1, true)] {
+        let settings = NormalizerSettings {
+            max_read_bytes: Some(budget),
+            ..Default::default()
+        };
+        let result = from_reader(Cursor::new(&payload), Some(settings)).unwrap();
+        assert_eq!(result.iter().any(|m| m.encoding() == "utf-8"), is_truncated);
+        assert!(result.iter().all(|m| m.is_truncated() == is_truncated));
+    }
+}
+
+#[test]
+fn test_from_reader_without_read_budget() {
+    let payload = "我没有埋怨，磋砣的只是一些时间。".as_bytes();
+    let result = from_reader(payload, None).unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+    assert_eq!(best_guess.raw(), payload);
+    assert!(!best_guess.is_truncated());
+}
+
+#[test]
+fn test_from_path_reads_whole_file() {
+    let payload = "Bсеки човек има право на образование. ".repeat(20_000);
+    assert!(payload.len() > MAX_PROCESSED_BYTES);
+    let mut path = std::env::temp_dir();
+    path.push(format!(
+        "charset-normalizer-whole-{}.txt",
+        std::process::id()
+    ));
+    fs::write(&path, &payload).unwrap();
+
+    let result = from_path(&path, None);
+    fs::remove_file(&path).unwrap();
+    let result = result.unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+    assert_eq!(best_guess.raw(), payload.as_bytes());
+    assert_eq!(best_guess.decoded_payload(), Some(payload.as_str()));
+    assert!(!best_guess.is_truncated());
+}
+
+#[test]
+fn test_typed_errors() {
+    let mut settings = NormalizerSettings::default();
+    settings.exclude_encodings.push(String::from("utf-57"));
+    assert!(matches!(
+        from_bytes(b"hello", Some(settings)),
+        Err(Error::UnknownEncoding(name)) if name == "utf-57"
+    ));
+
+    assert!(matches!(
+        from_path(Path::new("non-exists-file.txt"), None),
+        Err(Error::Io(_))
+    ));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_probing_wave() {
+    use crate::detector::Detector;
+    use crate::probing_wave;
+
+    let detector = Detector::new(NormalizerSettings::default()).unwrap();
+    // wave ends before an encoding similar to one probed earlier in it
+    assert_eq!(
+        probing_wave(
+            &detector,
+            &["euc-kr", "iso-8859-14", "big5", "windows-1254"],
+            &[]
+        ),
+        vec!["euc-kr", "iso-8859-14", "big5"]
+    );
+    // encodings similar to soft failed ones are left out
+    assert_eq!(
+        probing_wave(&detector, &["iso-8859-14", "euc-kr"], &["windows-1254"]),
+        vec!["euc-kr"]
+    );
+}
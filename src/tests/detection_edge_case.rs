@@ -1,4 +1,7 @@
+use crate::entity::{NormalizerSettings, SamplingStrategy};
 use crate::from_bytes;
+use crate::utils::encode;
+use encoding::EncoderTrap;
 
 #[test]
 fn test_undefined_unicode_ranges() {
@@ -26,3 +29,25 @@ fn test_undefined_unicode_ranges() {
         );
     }
 }
+
+#[test]
+fn test_non_ascii_islands_sampling() {
+    // mostly ASCII content with a single non-ASCII island, which is missed by evenly spaced chunks
+    let english =
+        "The quick brown fox jumps over the lazy dog while the farmer watches from the porch. ";
+    let russian = "В низинах на восточной стороне полуострова Люнген в основном встречаются слюдяные \
+        сланцы, филлиты и доломиты. Низменности на западной стороне в основном состоят из слюдяных сланцев. ";
+    let text = format!("{}{}{}", english.repeat(70), russian, english.repeat(170));
+    let payload = encode(&text, "windows-1251", EncoderTrap::Strict).unwrap();
+
+    let settings = NormalizerSettings {
+        sampling_strategy: SamplingStrategy::NonAsciiDense,
+        ..Default::default()
+    };
+    let result = from_bytes(&payload, Some(settings)).unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "windows-1251");
+    assert_eq!(
+        result.get_best().unwrap().decoded_payload(),
+        Some(text.as_str())
+    );
+}
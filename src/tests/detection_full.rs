@@ -1,6 +1,10 @@
-use crate::from_path;
+use crate::entity::{MessDetectorSettings, NormalizerSettings};
+use crate::error::Error;
+use crate::md::plugins::MessDetectorPlugin;
+use crate::md::structs::MessDetectorChar;
 use crate::tests::FILES_SAMPLES;
 use crate::utils::{get_large_test_datasets, is_multi_byte_encoding};
+use crate::{from_path, from_paths};
 use std::path::PathBuf;
 
 #[test]
@@ -50,10 +54,7 @@ fn test_largesets() {
 
         let result = result.unwrap();
         let best_guess = result.get_best();
-        let mut guess_encoding = "None";
-        if best_guess.is_some() {
-            guess_encoding = best_guess.unwrap().encoding();
-        }
+        let guess_encoding = best_guess.map_or("None", |m| m.encoding());
         let fail = !encoding.contains(&guess_encoding.to_string())
             && (guess_encoding == "None"
                 || encoding
@@ -63,3 +64,108 @@ fn test_largesets() {
         assert!(!fail, "Problems with {}", path);
     }
 }
+
+// both decoding backends give the same results
+#[cfg(feature = "encoding_rs")]
+#[test]
+fn test_largesets_backends_parity() {
+    use crate::detector::Detector;
+
+    let with_encoding = Detector::new(NormalizerSettings::default())
+        .unwrap()
+        .with_encoding_rs(false);
+    let with_encoding_rs = Detector::new(NormalizerSettings::default()).unwrap();
+    let summary = |detector: &Detector, path: &str| -> Vec<(String, String, String)> {
+        detector
+            .detect_path(&PathBuf::from(path))
+            .unwrap()
+            .iter()
+            .map(|m| {
+                (
+                    m.encoding().to_string(),
+                    format!("{:.4}", m.chaos()),
+                    format!("{:.4}", m.coherence()),
+                )
+            })
+            .collect()
+    };
+    for (path, _) in get_large_test_datasets().unwrap() {
+        assert_eq!(
+            summary(&with_encoding, &path),
+            summary(&with_encoding_rs, &path),
+            "Backends differ on {}",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_batch_detection() {
+    let mut paths: Vec<PathBuf> = FILES_SAMPLES
+        .iter()
+        .map(|(filename, _, _)| {
+            let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            path.push(format!("src/tests/data/samples/{}", filename));
+            path
+        })
+        .collect();
+    let missing_path = PathBuf::from("non-exists-file.txt");
+    paths.insert(1, missing_path.clone());
+
+    let mut processed = 0;
+    for (path, result) in from_paths(paths.clone(), None, 4) {
+        processed += 1;
+        if path == missing_path {
+            assert!(matches!(result, Err(Error::Io(_))));
+            continue;
+        }
+        let expected = from_path(&path, None).unwrap();
+        assert_eq!(
+            result.unwrap().get_best().map(|m| m.encoding().to_string()),
+            expected.get_best().map(|m| m.encoding().to_string()),
+            "Batch detection differs for {:?}",
+            path
+        );
+    }
+    assert_eq!(processed, paths.len());
+}
+
+struct PanickingPlugin;
+
+impl MessDetectorPlugin for PanickingPlugin {
+    fn eligible(&self, _: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, _: &MessDetectorChar) {
+        panic!("plugin failure");
+    }
+    fn ratio(&self) -> f32 {
+        0.0
+    }
+}
+
+#[test]
+fn test_from_paths_panics() {
+    let sample = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/data/samples");
+    let paths = vec![
+        sample.join("sample-french.txt"),
+        sample.join("sample-russian.txt"),
+    ];
+
+    // panic during detection of a file is an error of this file, others are still processed
+    let settings = NormalizerSettings {
+        mess_detector: MessDetectorSettings::default().plugin(|| Box::new(PanickingPlugin)),
+        ..Default::default()
+    };
+    let results: Vec<_> = from_paths(paths.clone(), Some(settings), 1).collect();
+    assert_eq!(results.len(), paths.len());
+    for (_, result) in results {
+        assert!(matches!(result, Err(Error::Panic(message)) if message == "plugin failure"));
+    }
+
+    // panic of paths iterator is propagated to the caller
+    let paths = paths
+        .into_iter()
+        .chain(std::iter::once_with(|| panic!("iterator failure")));
+    assert!(std::panic::catch_unwind(|| from_paths(paths, None, 2).count()).is_err());
+}
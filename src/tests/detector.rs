@@ -0,0 +1,209 @@
+use crate::detector::Detector;
+use crate::entity::{EncodingHints, NormalizerSettings};
+use crate::error::Error;
+use crate::from_bytes;
+use crate::tests::FILES_SAMPLES;
+use crate::utils::encode;
+use encoding::EncoderTrap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn test_detector_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Detector>();
+}
+
+#[test]
+fn test_detector_settings_validation() {
+    let settings = NormalizerSettings {
+        include_encodings: vec![String::from("latin1"), String::from("UTF8")],
+        exclude_encodings: vec![String::from("cp1252")],
+        ..Default::default()
+    };
+    let detector = Detector::new(settings).unwrap();
+    assert_eq!(
+        detector.settings().include_encodings,
+        vec!["windows-1252", "utf-8"]
+    );
+    assert_eq!(detector.settings().exclude_encodings, vec!["windows-1252"]);
+    assert_eq!(detector.encodings(), &["utf-8"]);
+
+    let tests = [
+        NormalizerSettings {
+            include_encodings: vec![String::from("utf-57")],
+            ..Default::default()
+        },
+        NormalizerSettings {
+            exclude_encodings: vec![String::from("utf-57")],
+            ..Default::default()
+        },
+    ];
+    for settings in tests {
+        assert!(matches!(
+            Detector::new(settings),
+            Err(Error::UnknownEncoding(_))
+        ));
+    }
+
+    let tests = [
+        NormalizerSettings {
+            steps: 0,
+            ..Default::default()
+        },
+        NormalizerSettings {
+            chunk_size: 0,
+            ..Default::default()
+        },
+    ];
+    for settings in tests {
+        assert!(matches!(
+            Detector::new(settings),
+            Err(Error::InvalidSettings(_))
+        ));
+    }
+}
+
+#[test]
+fn test_detector_same_as_from_bytes() {
+    let detector = Arc::new(Detector::new(NormalizerSettings::default()).unwrap());
+    let handles: Vec<_> = FILES_SAMPLES
+        .iter()
+        .take(8)
+        .map(|(sample_filename, _, _)| {
+            let detector = detector.clone();
+            thread::spawn(move || {
+                let mut sample_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+                sample_path.push(format!("src/tests/data/samples/{}", sample_filename));
+                let content = std::fs::read(&sample_path).unwrap();
+
+                let expected = from_bytes(&content, None).unwrap();
+                for result in [
+                    detector.detect(&content).unwrap(),
+                    detector.detect_path(&sample_path).unwrap(),
+                    detector.detect_reader(content.as_slice()).unwrap(),
+                ] {
+                    assert_eq!(
+                        result.get_best().map(|m| m.encoding()),
+                        expected.get_best().map(|m| m.encoding()),
+                        "Detector result differs for {}",
+                        sample_filename,
+                    );
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn test_detector_hints() {
+    let settings = NormalizerSettings::builder()
+        .exclude_encodings(["big5"])
+        .unwrap()
+        .hints(
+            EncodingHints::default()
+                .content_type("text/html; charset=\"koi8-u\"")
+                .prior("big5", 0.5)
+                .tld("example.co.jp"),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+    let detector = Detector::new(settings).unwrap();
+    // excluded encodings are not hinted, the most weighted go first
+    assert_eq!(
+        detector.priors(),
+        &[
+            ("koi8-u", 1.0),
+            ("shift_jis", 0.2),
+            ("euc-jp", 0.2),
+            ("iso-2022-jp", 0.2)
+        ]
+    );
+    assert_eq!(detector.prior("koi8-u"), 1.0);
+    assert_eq!(detector.prior("koi8-r"), 0.0);
+
+    // hinted encodings are probed right after prioritized ones
+    let (result, report) = detector
+        .detect_with_report(&encode("Привіт, як справи?", "koi8-u", EncoderTrap::Strict).unwrap())
+        .unwrap();
+    assert_eq!(
+        report.hinted_encodings,
+        vec!["koi8-u", "shift_jis", "euc-jp", "iso-2022-jp"]
+    );
+    let probed: Vec<&str> = report.candidates.iter().map(|(enc, _)| *enc).collect();
+    assert_eq!(
+        &probed[2..6],
+        &["koi8-u", "shift_jis", "euc-jp", "iso-2022-jp"]
+    );
+    assert_eq!(result.get_best().unwrap().encoding(), "koi8-u");
+    assert_eq!(result.get_best().unwrap().prior(), 1.0);
+}
+
+#[test]
+fn test_hints_ranking() {
+    let tests = [
+        (
+            "Привет, как дела? Всё хорошо",
+            "windows-1251",
+            EncodingHints::default().locale("ru_RU.UTF-8"),
+        ),
+        (
+            "Ελληνικά κείμενα",
+            "windows-1253",
+            EncodingHints::default().tld(".gr"),
+        ),
+        (
+            "Привет, как дела? Всё хорошо",
+            "koi8-u",
+            EncodingHints::default().prior("koi8-u", 0.8),
+        ),
+    ];
+    for (text, encoding, hints) in tests {
+        let bytes = encode(text, encoding, EncoderTrap::Strict).unwrap();
+        // too short text, so chaos and coherence cannot tell encodings apart
+        let result = from_bytes(&bytes, None).unwrap();
+        assert_ne!(result.get_best().unwrap().encoding(), encoding);
+
+        let settings = NormalizerSettings {
+            hints,
+            ..Default::default()
+        };
+        let result = from_bytes(&bytes, Some(settings)).unwrap();
+        let best_guess = result.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), encoding);
+        assert_eq!(best_guess.decoded_payload(), Some(text));
+    }
+}
+
+#[test]
+fn test_detector_is_cp_similar() {
+    let tests = [
+        ("iso-8859-14", "windows-1254", true),
+        ("iso-8859-14", "euc-kr", false),
+    ];
+    let detector = Detector::new(NormalizerSettings::default()).unwrap();
+    for test in &tests {
+        assert_eq!(detector.is_cp_similar(test.0, test.1), test.2);
+    }
+}
+
+#[test]
+fn test_detector_resolves_codecs() {
+    let settings = NormalizerSettings {
+        include_encodings: vec![String::from("cp1251"), String::from("utf-8")],
+        ..Default::default()
+    };
+    let detector = Detector::new(settings).unwrap();
+    assert_eq!(detector.codec("windows-1251").unwrap().name, "windows-1251");
+    assert!(detector.codec("utf-8").unwrap().is_multi_byte);
+    // encodings which are not probed have no codec
+    assert!(matches!(
+        detector.codec("koi8-r"),
+        Err(Error::UnknownEncoding(name)) if name == "koi8-r"
+    ));
+}
@@ -1,5 +1,10 @@
-use crate::entity::{CharsetMatch, CharsetMatches, CoherenceMatch, Language};
+use crate::entity::{
+    CharsetMatch, CharsetMatches, CoherenceMatch, ContentMode, EncodingHints, Language,
+    MessDetectorSettings, NormalizerSettings, SamplingStrategy,
+};
+use crate::error::Error;
 use ordered_float::OrderedFloat;
+use std::sync::Arc;
 
 #[test]
 fn test_charset_matches() {
@@ -8,7 +13,7 @@ fn test_charset_matches() {
     ///////////////////////////////////////////////////////////////////////////////////////////
 
     let mut c_matches = CharsetMatches::new(Some(vec![CharsetMatch::new(
-        (&[0xD0, 0xA2, 0xD0, 0xB5, 0xD1, 0x81, 0xD1, 0x82]).into(),
+        Arc::from(&[0xD0, 0xA2, 0xD0, 0xB5, 0xD1, 0x81, 0xD1, 0x82][..]),
         "utf-8",
         0.01,
         false,
@@ -28,7 +33,7 @@ fn test_charset_matches() {
 
     // append new CharsetMatch
     c_matches.append(CharsetMatch::new(
-        (&[0xD0, 0xA2, 0xD0, 0xB5, 0xD1, 0x81, 0xD1, 0x82]).into(),
+        Arc::from(&[0xD0, 0xA2, 0xD0, 0xB5, 0xD1, 0x81, 0xD1, 0x82][..]),
         "utf-16le",
         0.011,
         false,
@@ -86,7 +91,7 @@ fn test_charset_matches() {
     assert_eq!(
         c_matches[1],
         CharsetMatch::new(
-            (&[0xD0, 0xA2, 0xD0, 0xB5, 0xD1, 0x81, 0xD1, 0x82]).into(),
+            Arc::from(&[0xD0, 0xA2, 0xD0, 0xB5, 0xD1, 0x81, 0xD1, 0x82][..]),
             "utf-16le",
             0.044,
             true,
@@ -137,3 +142,224 @@ fn test_charset_matches() {
     // encoding_aliases
     assert!(c_matches[0].encoding_aliases().contains(&"unicode11utf8"));
 }
+
+#[test]
+fn test_charset_match_shared_payload() {
+    let payload: Arc<[u8]> = Arc::from("Qu'est ce que une étoile?".as_bytes());
+    let c_matches = CharsetMatches::new(Some(vec![
+        CharsetMatch::new(payload.clone(), "utf-8", 0.0, false, &vec![], None),
+        CharsetMatch::new(payload.clone(), "windows-1252", 0.1, false, &vec![], None),
+    ]));
+
+    // bytes are shared, not copied
+    for m in c_matches.iter() {
+        assert_eq!(m.raw().as_ptr(), payload.as_ptr());
+    }
+
+    // payload is decoded lazily, with each candidate encoding
+    assert_eq!(
+        c_matches[0].decoded_payload(),
+        Some("Qu'est ce que une étoile?")
+    );
+    assert_eq!(
+        c_matches[1].decoded_payload(),
+        Some("Qu'est ce que une Ã©toile?")
+    );
+}
+
+#[test]
+fn test_settings_builder() -> Result<(), Error> {
+    let settings = NormalizerSettings::builder()
+        .steps(3)?
+        .chunk_size(128)?
+        .threshold(0.1)?
+        .include_encodings(["utf-8", "latin1"])?
+        .exclude_encodings(vec![String::from("koi8-u")])?
+        .preemptive_behaviour(false)
+        .language_threshold(0.2)?
+        .enable_fallback(false)
+        .max_read_bytes(Some(4096))?
+        .sampling_strategy(SamplingStrategy::HeadTail)
+        .hints(EncodingHints::default().locale("ru_RU"))?
+        .content_mode(ContentMode::Auto)
+        .mess_detector(MessDetectorSettings::default().weight("UnprintablePlugin", 2.0))?
+        .build()?;
+    assert_eq!(
+        settings,
+        NormalizerSettings {
+            steps: 3,
+            chunk_size: 128,
+            threshold: OrderedFloat(0.1),
+            include_encodings: vec![String::from("utf-8"), String::from("latin1")],
+            exclude_encodings: vec![String::from("koi8-u")],
+            preemptive_behaviour: false,
+            language_threshold: OrderedFloat(0.2),
+            enable_fallback: false,
+            max_read_bytes: Some(4096),
+            sampling_strategy: SamplingStrategy::HeadTail,
+            hints: EncodingHints {
+                locale: Some(String::from("ru_RU")),
+                ..Default::default()
+            },
+            content_mode: ContentMode::Auto,
+            mess_detector: MessDetectorSettings {
+                weights: vec![(String::from("UnprintablePlugin"), 2.0)],
+                ..Default::default()
+            },
+        }
+    );
+    assert_eq!(
+        NormalizerSettings::builder().build().unwrap(),
+        NormalizerSettings::default()
+    );
+
+    // invalid values are rejected by setters
+    let builder = NormalizerSettings::builder;
+    let invalid = [
+        builder().steps(0),
+        builder().chunk_size(0),
+        builder().steps(usize::MAX),
+        builder().chunk_size(2).unwrap().steps(usize::MAX),
+        builder().steps(2).unwrap().chunk_size(usize::MAX),
+        builder().threshold(3.0),
+        builder().threshold(f32::NAN),
+        builder().language_threshold(-0.1),
+        builder().max_read_bytes(Some(0)),
+        builder().hints(EncodingHints::default().prior("koi8-r", 1.5)),
+        builder().mess_detector(MessDetectorSettings::default().weight("UnprintablePlugin", -1.0)),
+    ];
+    for result in invalid {
+        assert!(matches!(result, Err(Error::InvalidSettings(_))));
+    }
+    for result in [
+        builder().include_encodings(["utf-57"]),
+        builder().exclude_encodings(["utf-57"]),
+        builder().hints(EncodingHints::default().prior("utf-57", 0.5)),
+    ] {
+        assert!(matches!(result, Err(Error::UnknownEncoding(_))));
+    }
+
+    // settings are validated up front by detection as well
+    for settings in [
+        NormalizerSettings {
+            threshold: OrderedFloat(3.0),
+            ..Default::default()
+        },
+        NormalizerSettings {
+            steps: usize::MAX,
+            chunk_size: 2,
+            ..Default::default()
+        },
+    ] {
+        assert!(matches!(
+            crate::from_bytes(b"hello", Some(settings)),
+            Err(Error::InvalidSettings(_))
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_settings_serde() {
+    let settings = NormalizerSettings::builder()
+        .threshold(0.1)
+        .unwrap()
+        .sampling_strategy(SamplingStrategy::Random { seed: 42 })
+        .mess_detector(MessDetectorSettings::default().disable("ArchaicUpperLowerPlugin"))
+        .unwrap()
+        .build()
+        .unwrap();
+    let json = serde_json::to_string(&settings).unwrap();
+    assert_eq!(
+        serde_json::from_str::<NormalizerSettings>(&json).unwrap(),
+        settings
+    );
+
+    // missing fields get default values
+    let settings: NormalizerSettings = serde_json::from_str(
+        r#"{"threshold": 0.3, "exclude_encodings": ["big5"], "sampling_strategy": "non_ascii_dense"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        settings,
+        NormalizerSettings {
+            threshold: OrderedFloat(0.3),
+            exclude_encodings: vec![String::from("big5")],
+            sampling_strategy: SamplingStrategy::NonAsciiDense,
+            ..Default::default()
+        }
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_charset_match_serde() {
+    assert_eq!(
+        serde_json::to_string(&Language::Russian).unwrap(),
+        r#""Russian""#
+    );
+
+    let result = crate::from_bytes("Qu'est ce que une étoile?".as_bytes(), None).unwrap();
+    let best_guess = result.get_best().unwrap();
+    let json = serde_json::to_value(best_guess).unwrap();
+    assert_eq!(json["encoding"], "utf-8");
+    assert_eq!(
+        json["language"],
+        best_guess.most_probably_language().to_string()
+    );
+    assert_eq!(json["has_sig_or_bom"], false);
+    assert_eq!(json["is_truncated"], false);
+    assert!(json["encoding_aliases"]
+        .as_array()
+        .unwrap()
+        .contains(&"utf8".into()));
+    assert!(json["alternative_encodings"].as_array().unwrap().is_empty());
+    assert!(json["alphabets"]
+        .as_array()
+        .unwrap()
+        .contains(&"Latin-1 Supplement".into()));
+    // percents are formatted as CLI always did
+    assert_eq!(
+        json["coherence"],
+        format!("{:.1}", best_guess.coherence_percents())
+    );
+    assert_eq!(json["chaos"], "0.0");
+    assert!(json.get("is_preferred").is_none());
+
+    // keys go in documented order
+    let serialized = serde_json::to_string(best_guess).unwrap();
+    let keys = [
+        "encoding",
+        "encoding_aliases",
+        "alternative_encodings",
+        "language",
+        "alphabets",
+        "has_sig_or_bom",
+        "is_truncated",
+        "chaos",
+        "coherence",
+    ];
+    let positions: Vec<usize> = keys
+        .iter()
+        .map(|key| serialized.find(&format!("\"{}\":", key)).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+    // matches are serialized as list, from the most probable (preferred) one
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), result.len());
+    assert_eq!(json[0]["encoding"], "utf-8");
+    assert_eq!(json[0]["is_preferred"], true);
+
+    let result = crate::from_bytes(
+        b"\xcf\xf0\xe8\xe2\xe5\xf2, \xea\xe0\xea \xe4\xe5\xeb\xe0?",
+        None,
+    )
+    .unwrap();
+    assert!(result.len() > 1);
+    let json = serde_json::to_value(&result).unwrap();
+    for (index, m) in json.as_array().unwrap().iter().enumerate() {
+        assert_eq!(m["is_preferred"], index == 0);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::entity::NormalizerSettings;
+use crate::html::{prescan, SniffingStep};
+use crate::sniff_html;
+
+#[test]
+fn test_prescan() {
+    let tests = [
+        (b"<meta charset=\"utf-8\">".as_slice(), Some("utf-8")),
+        (b"<META CHARSET=KOI8-R>", Some("koi8-r")),
+        (b"<meta/charset='latin1'/>", Some("windows-1252")),
+        (
+            b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=euc-jp\">",
+            Some("euc-jp"),
+        ),
+        // attributes in any order, charset quoted in content
+        (
+            b"<meta content='text/html; charset=\"gbk\"' http-equiv='content-type'>",
+            Some("gbk"),
+        ),
+        // content without http-equiv pragma is ignored
+        (b"<meta content=\"text/html; charset=euc-jp\">", None),
+        // utf-16 & x-user-defined are replaced
+        (b"<meta charset=utf-16be>", Some("utf-8")),
+        (b"<meta charset=x-user-defined>", Some("windows-1252")),
+        // unknown label, then a valid one
+        (
+            b"<meta charset=utf-57><meta charset=iso-8859-2>",
+            Some("iso-8859-2"),
+        ),
+        // duplicated attribute is ignored
+        (b"<meta charset=big5 charset=gbk>", Some("big5")),
+        // tags, comments and attributes which look like meta are skipped
+        (b"<!-- <meta charset=big5> --><p>", None),
+        (b"<!-- <meta charset=big5 --> -->", None),
+        // empty comments
+        (b"<!--><meta charset=big5>-->", Some("big5")),
+        (b"<!---><meta charset=big5>", Some("big5")),
+        (b"<div title=\"<meta charset=big5>\"></div>", None),
+        (b"<?xml version=\"1.0\" encoding=\"big5\"?>", None),
+        (b"<metadata charset=big5>", None),
+        // incomplete tag
+        (b"<meta charset=big5", None),
+    ];
+    for (body, expected) in tests {
+        assert_eq!(prescan(body), expected, "{}", String::from_utf8_lossy(body));
+    }
+
+    // only the first 1024 bytes are prescanned
+    let mut body = vec![b' '; 1020];
+    body.extend(b"<meta charset=big5>");
+    assert_eq!(prescan(&body), None);
+    assert_eq!(prescan(&body[20..]), Some("big5"));
+}
+
+#[test]
+fn test_sniff_html() {
+    let cyrillic = b"<p>\xcf\xf0\xe8\xe2\xe5\xf2, \xea\xe0\xea \xe4\xe5\xeb\xe0?</p>";
+    let with_meta = [b"<meta charset=koi8-r>".as_slice(), cyrillic].concat();
+    let with_bom = [
+        b"\xef\xbb\xbf<meta charset=koi8-r>".as_slice(),
+        "<p>Привет</p>".as_bytes(),
+    ]
+    .concat();
+
+    let tests = [
+        (
+            with_bom.as_slice(),
+            Some("text/html; charset=koi8-r"),
+            SniffingStep::ByteOrderMark,
+            "utf-8",
+        ),
+        (
+            with_meta.as_slice(),
+            Some("text/html; charset=windows-1251"),
+            SniffingStep::TransportLayer,
+            "windows-1251",
+        ),
+        (
+            with_meta.as_slice(),
+            Some("text/html"),
+            SniffingStep::MetaPrescan,
+            "koi8-r",
+        ),
+        (
+            with_meta.as_slice(),
+            None,
+            SniffingStep::MetaPrescan,
+            "koi8-r",
+        ),
+        // transport charset which does not fit the body is skipped
+        (
+            with_meta.as_slice(),
+            Some("text/html; charset=utf-8"),
+            SniffingStep::MetaPrescan,
+            "koi8-r",
+        ),
+        // only WHATWG labels are known at transport layer
+        (
+            with_meta.as_slice(),
+            Some("text/html; charset=cp437"),
+            SniffingStep::MetaPrescan,
+            "koi8-r",
+        ),
+        (
+            with_meta.as_slice(),
+            Some("text/html; Charset=\"CP1251\""),
+            SniffingStep::TransportLayer,
+            "windows-1251",
+        ),
+        (
+            b"<p>Qu'est ce que une \xc3\xa9toile?</p>".as_slice(),
+            None,
+            SniffingStep::Detection,
+            "utf-8",
+        ),
+    ];
+    for (body, content_type, step, encoding) in tests {
+        let result = sniff_html(body, content_type, None).unwrap();
+        assert_eq!(result.decided_by, step, "{:?}", content_type);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches.get_best().unwrap().encoding(), encoding);
+    }
+
+    // excluded encodings are skipped
+    let settings = NormalizerSettings {
+        exclude_encodings: vec![String::from("koi8-r")],
+        ..Default::default()
+    };
+    let result = sniff_html(&with_meta, None, Some(settings)).unwrap();
+    assert_eq!(result.decided_by, SniffingStep::Detection);
+    assert_ne!(result.matches.get_best().unwrap().encoding(), "koi8-r");
+}
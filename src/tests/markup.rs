@@ -0,0 +1,111 @@
+use crate::entity::{ContentMode, NormalizerSettings};
+use crate::from_bytes_with_report;
+use crate::markup::{looks_like_markup, strip_markup, strip_markup_text};
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+#[test]
+fn test_strip_markup() {
+    let tests = [
+        ("<p>Hello <b>world</b></p>", "Hello world "),
+        ("<a href=\"/x?a=1&amp;b=2\" title='a > b'>link</a>", "link "),
+        ("<!-- <p>comment</p> -->text", "text"),
+        ("<!DOCTYPE html><?xml version=\"1.0\"?>text", "text"),
+        (
+            "<script type=\"text/javascript\">if (a < b) {}</script>x<STYLE>p {}</STYLE>y",
+            "x y",
+        ),
+        ("<script src=\"a.js\"/>text", "text"),
+        ("<Script>a</sCrIpT >b<script>c</script", "b "),
+        (
+            "caf&eacute; &#233;t&#xE9; &lt;tag&gt; &unknown; &amp",
+            "caf été <tag>  &amp",
+        ),
+        ("a < b > c", "a < b > c"),
+        ("unclosed <tag", "unclosed <tag"),
+    ];
+    for (text, expected) in tests {
+        assert_eq!(strip_markup_text(text), expected, "{}", text);
+    }
+
+    // non-ascii characters references are dropped from raw bytes, offsets point to original content
+    let bytes = b"<p class=\"x\">a&nbsp;&#65;\xe9<br/>b</p>";
+    let stripped = strip_markup(bytes, false);
+    assert_eq!(stripped.content, b"aA\xe9 b ");
+    for (offset, original_offset) in [(0, 13), (1, 20), (2, 25), (3, 26), (4, 31), (5, 32)] {
+        assert_eq!(stripped.original_offset(offset), original_offset);
+    }
+    // offsets within removed markup go to the position where it has been removed
+    for (original_offset, offset) in [
+        (0, 0),
+        (5, 0),
+        (13, 0),
+        (14, 1),
+        (22, 2),
+        (25, 2),
+        (26, 3),
+        (40, 6),
+    ] {
+        assert_eq!(stripped.stripped_offset(original_offset), offset);
+    }
+}
+
+#[test]
+fn test_looks_like_markup() {
+    let tests = [
+        (b"<html><body>text</body></html>".as_slice(), true),
+        (b"\xef\xbb\xbf\n  <?xml version=\"1.0\"?><a>text</a>", true),
+        (b"<p>only one tag", false),
+        (b"Plain text with <tags> inside </tags>", false),
+        (b"a < b and c > d", false),
+    ];
+    for (bytes, expected) in tests {
+        assert_eq!(
+            looks_like_markup(bytes),
+            expected,
+            "{}",
+            String::from_utf8_lossy(bytes)
+        );
+    }
+}
+
+#[test]
+fn test_markup_content_mode() {
+    let script = "<script>var a={b:[1,2,3]};if(a<3&&b>2){c(\"#x\",'.y');}\
+        $(function(){$('.z').on('click',function(e){e.preventDefault();});});</script>";
+    let nav = "<div class=\"nav\"><a href=\"/x?y=1&amp;z=2\">[Home]</a> | <a href=\"/about\">{About}</a></div>";
+    let html = format!(
+        "<html><head><style>body{{color:red;font:12px/1.5 serif}}</style>{script}</head>\
+        <body>{nav}{nav}<p>Zażółć gęślą jaźń, to jest zdanie po polsku z wieloma znakami.</p>\
+        {nav}{script}</body></html>"
+    );
+    let bytes = encode(&html, "windows-1250", EncoderTrap::Strict).unwrap();
+
+    let detect = |content_mode| {
+        from_bytes_with_report(
+            &bytes,
+            Some(NormalizerSettings {
+                content_mode,
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    };
+    let (plain_text, report) = detect(ContentMode::PlainText);
+    assert!(!report.is_markup);
+    for content_mode in [ContentMode::Markup, ContentMode::Auto] {
+        let (markup, report) = detect(content_mode);
+        assert!(report.is_markup);
+
+        // match still decodes the whole content
+        let best_guess = markup.get_best().unwrap();
+        assert_eq!(best_guess.encoding(), "windows-1250");
+        assert_eq!(best_guess.decoded_payload(), Some(html.as_str()));
+
+        // without tags wrong encodings look worse
+        assert!(
+            markup.get_by_encoding("iso-8859-1").unwrap().chaos()
+                > plain_text.get_by_encoding("iso-8859-1").unwrap().chaos()
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use crate::entity::{MessDetectorSettings, NormalizerSettings};
+use crate::error::Error;
+use crate::from_bytes;
+use crate::md::plugins::MessDetectorPlugin;
 use crate::md::structs::{MessDetectorChar, MessDetectorCharFlags};
 use crate::md::*;
 use crate::utils::{decode, get_large_test_datasets};
@@ -5,6 +9,7 @@ use encoding::DecoderTrap;
 use ordered_float::OrderedFloat;
 use std::fs::File;
 use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[test]
 fn test_mess_ratio() {
@@ -56,6 +61,194 @@ fn test_datasets_mess_ratio() {
     }
 }
 
+// Plugin which finds any text messy
+#[derive(Default)]
+struct AlwaysMessyPlugin;
+
+impl MessDetectorPlugin for AlwaysMessyPlugin {
+    fn eligible(&self, _: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, _: &MessDetectorChar) {}
+    fn ratio(&self) -> f32 {
+        0.5
+    }
+}
+
+// Plugin which takes the name of a built-in one
+#[derive(Default)]
+struct ImpostorPlugin;
+
+impl MessDetectorPlugin for ImpostorPlugin {
+    fn name(&self) -> &str {
+        "UnprintablePlugin"
+    }
+    fn eligible(&self, _: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, _: &MessDetectorChar) {}
+    fn ratio(&self) -> f32 {
+        0.0
+    }
+}
+
+#[test]
+fn test_configured_mess_ratio() {
+    let text = String::from("´Á¥½³ø§i --  ±i®Ìºû, ³¯·Ø©v");
+    let threshold = OrderedFloat(1.0);
+    let ratio = mess_ratio(text.clone(), Some(threshold));
+    assert!(ratio > 0.0);
+
+    let md_ratio = |settings: MessDetectorSettings| {
+        assert!(settings.validate().is_ok());
+        configured_mess_ratio(text.clone(), threshold, &settings).0
+    };
+    assert_eq!(md_ratio(MessDetectorSettings::default()), ratio);
+    let all_disabled = builtin_plugins()
+        .iter()
+        .fold(MessDetectorSettings::default(), |settings, d| {
+            settings.disable(d.name())
+        });
+    assert_eq!(md_ratio(all_disabled.clone()), 0.0);
+    let all_doubled = builtin_plugins()
+        .iter()
+        .fold(MessDetectorSettings::default(), |settings, d| {
+            settings.weight(d.name(), 2.0)
+        });
+    assert!((md_ratio(all_doubled) - 2.0 * ratio).abs() < 1e-6);
+    let extra = all_disabled.plugin(|| Box::new(AlwaysMessyPlugin));
+    assert_eq!(md_ratio(extra.clone()), 0.5);
+    assert_eq!(md_ratio(extra.weight("AlwaysMessyPlugin", 0.1)), 0.05);
+
+    // unknown plugins, negative weights and plugins with names taken are rejected
+    for settings in [
+        MessDetectorSettings::default().disable("AlwaysMessyPlugin"),
+        MessDetectorSettings::default().weight("AlwaysMessyPlugin", 1.0),
+        MessDetectorSettings::default().weight("UnprintablePlugin", -1.0),
+        MessDetectorSettings::default().plugin(|| Box::new(ImpostorPlugin)),
+        MessDetectorSettings::default()
+            .plugin(|| Box::new(AlwaysMessyPlugin))
+            .plugin(|| Box::new(AlwaysMessyPlugin)),
+    ] {
+        assert!(matches!(
+            settings.validate(),
+            Err(Error::InvalidSettings(_))
+        ));
+    }
+
+    // extra plugin takes part in detection
+    let settings = NormalizerSettings {
+        mess_detector: MessDetectorSettings::default().plugin(|| Box::new(AlwaysMessyPlugin)),
+        enable_fallback: false,
+        ..Default::default()
+    };
+    let content = "Всички хора се раждат свободни и равни".as_bytes();
+    assert!(from_bytes(content, None).unwrap().get_best().is_some());
+    assert!(from_bytes(content, Some(settings.clone()))
+        .unwrap()
+        .get_best()
+        .is_none());
+    let settings = NormalizerSettings {
+        threshold: OrderedFloat(0.6),
+        ..settings
+    };
+    assert!(from_bytes(content, Some(settings))
+        .unwrap()
+        .get_best()
+        .is_some());
+}
+
+// Plugin which finds any text messy, created plugins are counted
+#[derive(Default)]
+struct CountedPlugin;
+
+static CREATED_PLUGINS: AtomicUsize = AtomicUsize::new(0);
+
+impl MessDetectorPlugin for CountedPlugin {
+    fn name(&self) -> &str {
+        "CountedPlugin"
+    }
+    fn eligible(&self, _: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, _: &MessDetectorChar) {}
+    fn ratio(&self) -> f32 {
+        0.5
+    }
+}
+
+#[test]
+fn test_plugin_factory() {
+    let create = || -> Box<dyn MessDetectorPlugin> {
+        CREATED_PLUGINS.fetch_add(1, Ordering::SeqCst);
+        Box::new(CountedPlugin)
+    };
+
+    // name is known since factory is created, so it is not asked again
+    let settings = MessDetectorSettings::default()
+        .plugin(create)
+        .weight("CountedPlugin", 0.2);
+    assert_eq!(CREATED_PLUGINS.load(Ordering::SeqCst), 1);
+    assert_eq!(settings.plugins[0].name(), "CountedPlugin");
+    assert!(settings.validate().is_ok());
+    assert!(format!("{:?}", settings).contains("MessDetectorPluginFactory(CountedPlugin)"));
+    assert_eq!(CREATED_PLUGINS.load(Ordering::SeqCst), 1);
+
+    // ratios of custom settings are cached too, clones share the cache
+    let text = String::from("Всички хора се раждат свободни и равни");
+    let ratio = configured_mess_ratio(text.clone(), OrderedFloat(1.0), &settings).0;
+    assert_eq!(CREATED_PLUGINS.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        configured_mess_ratio(text.clone(), OrderedFloat(1.0), &settings.clone()).0,
+        ratio
+    );
+    assert_eq!(CREATED_PLUGINS.load(Ordering::SeqCst), 2);
+    let reweighted = settings.clone().weight("CountedPlugin", 0.4);
+    assert!(configured_mess_ratio(text, OrderedFloat(1.0), &reweighted).0 > ratio);
+    assert_eq!(CREATED_PLUGINS.load(Ordering::SeqCst), 3);
+
+    // settings with extra plugins can be used inside of catch_unwind
+    let settings = NormalizerSettings {
+        mess_detector: settings,
+        ..Default::default()
+    };
+    let content = "Всички хора се раждат свободни и равни".as_bytes();
+    let result = std::panic::catch_unwind(|| from_bytes(content, Some(settings.clone())));
+    assert!(result.is_ok_and(|result| result.is_ok()));
+}
+
+#[test]
+fn test_mess_breakdown() {
+    let breakdowns = vec![
+        vec![(String::from("A"), 0.2), (String::from("B"), 0.0)],
+        vec![(String::from("A"), 0.4), (String::from("B"), 0.1)],
+    ];
+    assert_eq!(
+        mean_mess_breakdown(&breakdowns),
+        vec![(String::from("A"), 0.3), (String::from("B"), 0.05)]
+    );
+    assert!(mean_mess_breakdown(&[]).is_empty());
+
+    // breakdown of match sums up to its chaos
+    let settings = NormalizerSettings {
+        mess_detector: MessDetectorSettings::default()
+            .plugin(|| Box::new(AlwaysMessyPlugin))
+            .weight("AlwaysMessyPlugin", 0.1),
+        ..Default::default()
+    };
+    let content = "Всички хора се раждат свободни и равни".as_bytes();
+    let result = from_bytes(content, Some(settings)).unwrap();
+    let best_guess = result.get_best().unwrap();
+    let breakdown = best_guess.mess_breakdown();
+    let names: Vec<&str> = breakdown.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names.len(), builtin_plugins().len() + 1);
+    assert!(names.contains(&"SuperWeirdWordPlugin"));
+    assert!(names.contains(&"SuspiciousRangePlugin"));
+    assert!(breakdown.contains(&(String::from("AlwaysMessyPlugin"), 0.05)));
+    let sum: f32 = breakdown.iter().map(|(_, ratio)| ratio).sum();
+    assert!((sum - best_guess.chaos()).abs() < 1e-6);
+}
+
 #[test]
 fn test_is_accentuated() {
     let tests = [
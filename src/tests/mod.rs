@@ -2,12 +2,19 @@
 use crate::entity::Language;
 use once_cell::sync::Lazy;
 mod cd;
+mod codecs;
+mod declarations;
 mod detection_base;
 mod detection_edge_case;
 mod detection_full;
 mod detection_large_payload;
+mod detector;
 mod entity;
+mod html;
+mod markup;
 mod md;
+mod report;
+mod stream;
 mod utils;
 
 pub static FILES_SAMPLES: Lazy<Vec<(&'static str, Vec<&'static str>, &'static Language)>> =
@@ -0,0 +1,188 @@
+use crate::consts::IANA_SUPPORTED;
+use crate::entity::{Language, MessDetectorSettings, NormalizerSettings};
+use crate::from_bytes_with_report;
+use crate::md::plugins::MessDetectorPlugin;
+use crate::md::structs::MessDetectorChar;
+use crate::report::{CandidateOutcome, Utf7Verdict};
+use crate::utils::encode;
+use encoding::EncoderTrap;
+
+#[test]
+fn test_report_lists_every_encoding() {
+    let (result, report) =
+        from_bytes_with_report("Qu'est ce que une étoile?".as_bytes(), None).unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+
+    assert_eq!(report.candidates.len(), IANA_SUPPORTED.len());
+    for encoding in IANA_SUPPORTED.iter() {
+        assert!(
+            report.outcome(encoding).is_some(),
+            "{} is missing",
+            encoding
+        );
+    }
+
+    assert_eq!(report.prioritized_encodings, vec!["ascii", "utf-8"]);
+    assert_eq!(report.early_return.as_deref(), Some("utf-8"));
+    assert_eq!(report.accepted(), vec!["utf-8"]);
+    assert_eq!(
+        report.outcome("ascii"),
+        Some(&CandidateOutcome::HardFailure { offset: 18 })
+    );
+    assert_eq!(
+        report.outcome("utf-16le"),
+        Some(&CandidateOutcome::RequiresBom)
+    );
+    assert_eq!(
+        report.outcome("windows-1252"),
+        Some(&CandidateOutcome::NotTested)
+    );
+}
+
+#[test]
+fn test_report_candidates_fate() {
+    let payload = encode(
+        "Всеки човек има право на образование. Образованието трябва да бъде безплатно, \
+        поне що се отнася до началното и основното образование.",
+        "windows-1251",
+        EncoderTrap::Strict,
+    )
+    .unwrap();
+    let settings = NormalizerSettings {
+        exclude_encodings: vec![String::from("koi8-u")],
+        ..Default::default()
+    };
+    let (result, report) = from_bytes_with_report(&payload, Some(settings)).unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "windows-1251");
+    assert!(report.early_return.is_none());
+    assert_eq!(report.outcome("koi8-u"), Some(&CandidateOutcome::Excluded));
+    assert!(matches!(
+        report.outcome("utf-8"),
+        Some(CandidateOutcome::HardFailure { offset: 0 })
+    ));
+
+    // every accepted encoding is in results, with the same mess ratio
+    for encoding in report.accepted() {
+        let Some(CandidateOutcome::Accepted {
+            mean_mess_ratio, ..
+        }) = report.outcome(encoding)
+        else {
+            unreachable!()
+        };
+        assert!(result
+            .iter()
+            .flat_map(|m| std::iter::once(m).chain(m.submatch().iter()))
+            .any(|m| m.encoding() == encoding && m.chaos() == *mean_mess_ratio));
+    }
+    let Some(CandidateOutcome::Accepted {
+        coherence_matches, ..
+    }) = report.outcome("windows-1251")
+    else {
+        panic!("windows-1251 should be accepted");
+    };
+    assert!(coherence_matches
+        .iter()
+        .any(|(language, _)| *language == &Language::Bulgarian));
+
+    // soft failures have mess ratios, similar code pages point to one of them
+    for (encoding, outcome) in &report.candidates {
+        match outcome {
+            CandidateOutcome::SoftFailure { mess_ratios } => {
+                assert!(!mess_ratios.is_empty(), "{} has no mess ratios", encoding)
+            }
+            CandidateOutcome::SimilarToSoftFailure { similar_to } => assert!(
+                matches!(
+                    report.outcome(similar_to),
+                    Some(CandidateOutcome::SoftFailure { .. })
+                        | Some(CandidateOutcome::HardFailure { .. })
+                ),
+                "{} is similar to not failed {}",
+                encoding,
+                similar_to
+            ),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_report_fallback() {
+    let settings = NormalizerSettings {
+        include_encodings: vec![String::from("ascii"), String::from("utf-8")],
+        threshold: 0.0.into(),
+        ..Default::default()
+    };
+    let (result, report) =
+        from_bytes_with_report(b"{\"hello\": \"world\"}", Some(settings)).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(report.prepared_fallbacks, vec!["ascii", "utf-8"]);
+    assert_eq!(report.used_fallback.as_deref(), Some("ascii"));
+    assert_eq!(report.outcome("big5"), Some(&CandidateOutcome::Excluded));
+}
+
+// Plugin which finds any text messy
+struct MessyPlugin;
+
+impl MessDetectorPlugin for MessyPlugin {
+    fn eligible(&self, _: &MessDetectorChar) -> bool {
+        true
+    }
+    fn feed(&mut self, _: &MessDetectorChar) {}
+    fn ratio(&self) -> f32 {
+        1.0
+    }
+}
+
+#[test]
+fn test_report_utf7_verdict() {
+    let tests = [
+        (
+            "Fran+AOc-ois a bu un caf+AOk- au march+AOk- de No+AOs-l.",
+            "utf-7",
+            Utf7Verdict::Plausible {
+                shift_sequences: 4,
+                mess_ratio: 0.0,
+            },
+        ),
+        (
+            "Plain text without any shift sequence.",
+            "ascii",
+            Utf7Verdict::NoShiftSequence,
+        ),
+        (
+            "C++ is not that plain, but it is still ascii.",
+            "ascii",
+            Utf7Verdict::InvalidShiftSequence { offset: 1 },
+        ),
+    ];
+    for (payload, expected_encoding, expected_verdict) in tests {
+        let (result, report) = from_bytes_with_report(payload.as_bytes(), None).unwrap();
+        assert_eq!(result.get_best().unwrap().encoding(), expected_encoding);
+        assert_eq!(report.utf7_verdict, Some(expected_verdict));
+        if expected_encoding == "ascii" {
+            assert_eq!(
+                report.outcome("utf-7"),
+                Some(&CandidateOutcome::RequiresBom)
+            );
+        }
+    }
+
+    // content with non-ascii bytes is out of question
+    let (_, report) = from_bytes_with_report("Noël".as_bytes(), None).unwrap();
+    assert_eq!(report.utf7_verdict, None);
+
+    // decoded text is measured by configured mess detection plugins
+    let settings = NormalizerSettings {
+        mess_detector: MessDetectorSettings::default().plugin(|| Box::new(MessyPlugin)),
+        ..Default::default()
+    };
+    let (_, report) = from_bytes_with_report(
+        b"Fran+AOc-ois a bu un caf+AOk- au march+AOk- de No+AOs-l.",
+        Some(settings),
+    )
+    .unwrap();
+    assert!(matches!(
+        report.utf7_verdict,
+        Some(Utf7Verdict::Implausible { .. })
+    ));
+}
@@ -0,0 +1,175 @@
+use crate::entity::NormalizerSettings;
+use crate::from_bytes;
+use crate::stream::StreamingDetector;
+use crate::tests::FILES_SAMPLES;
+use crate::utils::encode;
+use encoding::EncoderTrap;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_streaming_same_as_from_bytes() {
+    for (filename, encodings, _) in FILES_SAMPLES.iter() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push(format!("src/tests/data/samples/{}", filename));
+        let payload = fs::read(path).unwrap();
+
+        let mut detector = StreamingDetector::new(None).unwrap();
+        for piece in payload.chunks(333) {
+            detector.feed(piece);
+        }
+        assert_eq!(detector.len(), payload.len());
+        let result = detector.finish().unwrap();
+        let best_guess = result.get_best().unwrap();
+        assert!(
+            encodings.contains(&best_guess.encoding()),
+            "Streaming detection has failed upon '{}', {} NOT IN {:?}",
+            filename,
+            best_guess.encoding(),
+            encodings
+        );
+        assert!(payload.starts_with(best_guess.raw()));
+        assert_eq!(
+            best_guess.encoding(),
+            from_bytes(&payload, None)
+                .unwrap()
+                .get_best()
+                .unwrap()
+                .encoding()
+        );
+    }
+}
+
+#[test]
+fn test_streaming_confident_on_bom() {
+    let mut detector = StreamingDetector::new(None).unwrap();
+    assert!(!detector.is_confident());
+    detector.feed(b"\xef\xbb");
+    assert!(!detector.is_confident());
+    detector.feed(b"\xbfhello world");
+    assert!(detector.is_confident());
+    let result = detector.finish().unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+    assert!(result.get_best().unwrap().bom());
+}
+
+#[test]
+fn test_streaming_not_confident_on_ascii_prefix() {
+    // ASCII prefix may be followed by anything, so it can't be a clear winner
+    let mut detector = StreamingDetector::new(None).unwrap();
+    detector.feed(b"hello simple ascii ".repeat(500).as_slice());
+    assert!(!detector.is_confident());
+    detector.feed("我没有埋怨，磋砣的只是一些时间。".as_bytes());
+    let result = detector.finish().unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+}
+
+#[test]
+fn test_streaming_confident_on_multibyte_split_sequences() {
+    // pieces are cut in the middle of multibyte sequences
+    let payload = "Bсеки човек има право на образование. ".repeat(200);
+    let mut detector = StreamingDetector::new(None).unwrap();
+    for piece in payload.as_bytes().chunks(7) {
+        detector.feed(piece);
+    }
+    assert!(detector.is_confident());
+    let result = detector.finish().unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+    // only sampled prefix is kept once detector is confident
+    let decoded_payload = result.get_best().unwrap().decoded_payload().unwrap();
+    assert!(!decoded_payload.is_empty());
+    assert!(payload.starts_with(decoded_payload));
+}
+
+#[test]
+fn test_streaming_empty() {
+    let detector = StreamingDetector::new(None).unwrap();
+    assert!(detector.is_empty());
+    let result = detector.finish().unwrap();
+    assert_eq!(result.get_best().unwrap().encoding(), "utf-8");
+}
+
+#[test]
+fn test_streaming_buffer_is_capped() {
+    let payload = "Qu'est ce que une étoile? ".repeat(400);
+    let settings = NormalizerSettings {
+        max_read_bytes: Some(3000),
+        ..Default::default()
+    };
+    let mut detector = StreamingDetector::new(Some(settings)).unwrap();
+    for piece in payload.as_bytes().chunks(100) {
+        detector.feed(piece);
+    }
+    assert_eq!(detector.len(), payload.len());
+    let result = detector.finish().unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-8");
+    assert!(best_guess.raw().len() <= 3000);
+    assert!(payload.as_bytes().starts_with(best_guess.raw()));
+}
+
+#[test]
+fn test_streaming_confidence_is_lost() {
+    // clear winner does not fit the rest of payload
+    let payload = "Bсеки човек има право на образование. ".repeat(200);
+    let mut detector = StreamingDetector::new(None).unwrap();
+    for piece in payload.as_bytes().chunks(500) {
+        detector.feed(piece);
+    }
+    assert!(detector.is_confident());
+    detector.feed(b"\xff\xfe\xfd");
+    assert!(!detector.is_confident());
+    let result = detector.finish().unwrap();
+    assert!(result.iter().all(|m| m.encoding() != "utf-8"));
+}
+
+#[test]
+fn test_streaming_utf7_split_shift_sequences() {
+    // shift sequences are longer than any piece
+    let text = "Все люди рождаются свободными и равными в своем достоинстве и правах. ".repeat(100);
+    let payload = encode(&text, "utf-7", EncoderTrap::Strict).unwrap();
+    let mut detector = StreamingDetector::new(None).unwrap();
+    for piece in payload.chunks(3) {
+        detector.feed(piece);
+    }
+    assert!(detector.is_confident());
+    let result = detector.finish().unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "utf-7");
+    assert!(text.starts_with(best_guess.decoded_payload().unwrap()));
+}
+
+#[test]
+fn test_streaming_buffer_is_capped_when_confident() {
+    let payload = "Bсеки човек има право на образование. ".repeat(200);
+    let settings = NormalizerSettings {
+        max_read_bytes: Some(1000),
+        ..Default::default()
+    };
+    let mut detector = StreamingDetector::new(Some(settings)).unwrap();
+    for piece in payload.as_bytes().chunks(100) {
+        detector.feed(piece);
+    }
+    assert!(detector.is_confident());
+    let result = detector.finish().unwrap();
+    assert!(result.get_best().unwrap().raw().len() <= 1000);
+}
+
+#[test]
+fn test_streaming_buffering_goes_on_after_rejection() {
+    // pieces after rejected winner are still used for detection
+    let settings = NormalizerSettings {
+        steps: 1,
+        chunk_size: 512,
+        ..Default::default()
+    };
+    let mut detector = StreamingDetector::new(Some(settings)).unwrap();
+    detector.feed(b"\xef\xbb\xbfhello world");
+    assert!(detector.is_confident());
+    detector.feed(b"\xff\xfe\xfd");
+    assert!(!detector.is_confident());
+    detector.feed(b" and the rest");
+    let result = detector.finish().unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert!(best_guess.raw().ends_with(b" and the rest"));
+}
@@ -1,7 +1,9 @@
-use crate::entity::NormalizerSettings;
+use crate::consts::{IANA_SUPPORTED, LOCALE_ENCODINGS, OEM_SNIFF_SIZE, TLD_LOCALES};
+use crate::entity::{NormalizerSettings, SamplingStrategy};
+use crate::error::Error;
 use crate::tests::FILES_SAMPLES;
 use crate::utils::*;
-use encoding::DecoderTrap;
+use encoding::{DecoderTrap, EncoderTrap};
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
@@ -76,6 +78,25 @@ fn test_identify_sig_or_bom() {
             b"\xef\xbb\xbf lol kek".as_slice(),
             Some("utf-8".to_string()),
         ),
+        (
+            b"\xff\xfel\x00o\x00l\x00".as_slice(),
+            Some("utf-16le".to_string()),
+        ),
+        (
+            b"\xff\xfe\x00\x00l\x00\x00\x00".as_slice(),
+            Some("utf-32le".to_string()),
+        ),
+        (
+            b"\x00\x00\xfe\xff\x00\x00\x00l".as_slice(),
+            Some("utf-32be".to_string()),
+        ),
+        // utf-16le BOM followed by U+0000
+        (
+            b"\xff\xfe\x00\x00l\x00o\x00l\x00".as_slice(),
+            Some("utf-16le".to_string()),
+        ),
+        // ambiguous mark alone is taken for utf-32le one
+        (b"\xff\xfe\x00\x00".as_slice(), Some("utf-32le".to_string())),
         (b"lol kek".as_slice(), None),
     ];
     for test in &tests {
@@ -84,26 +105,81 @@ fn test_identify_sig_or_bom() {
 }
 
 #[test]
-fn test_iana_name() {
+fn test_identify_utf16_without_bom() {
+    let text = "Bonjour, voici le résumé de la réunion d'hier.\r\nСпасибо! 😀";
+    let le = encode(text, "utf-16le", EncoderTrap::Strict).unwrap();
+    let be = encode(text, "utf-16be", EncoderTrap::Strict).unwrap();
+    assert_eq!(identify_utf16_without_bom(&le), Some("utf-16le"));
+    assert_eq!(identify_utf16_without_bom(&be), Some("utf-16be"));
+
     let tests = [
-        ("utf8", Some("utf-8")),
-        ("csibm866", Some("ibm866")),
-        ("whatever", None),
-        ("korean", Some("euc-kr")),
+        // single-byte text
+        b"Bonjour, voici le r\xe9sum\xe9".as_slice(),
+        // NUL bytes on both parities
+        b"\x01\x00\x00\x00\x02\x00\x00\x00\x03\x00\x00\x00".as_slice(),
+        // unpaired surrogate
+        b"a\x00b\x00\x00\xdcc\x00".as_slice(),
+        // control characters
+        b"\x01\x00\x02\x00\x03\x00a\x00\x04\x00\x05\x00".as_slice(),
+        // PNG header
+        b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x01\x00\x00\x00\x01\x00\x08\x06\x00\x00\x00"
+            .as_slice(),
     ];
-    for test in &tests {
-        assert_eq!(iana_name(test.0), test.1);
+    for test in tests {
+        assert_eq!(identify_utf16_without_bom(test), None, "{:?}", test);
     }
 }
 
 #[test]
-fn test_is_cp_similar() {
+fn test_identify_utf32_without_bom() {
+    let text = "Bonjour, voici le résumé.\r\nСпасибо! 😀";
+    let le = encode(text, "utf-32le", EncoderTrap::Strict).unwrap();
+    let be = encode(text, "utf-32be", EncoderTrap::Strict).unwrap();
+    assert_eq!(identify_utf32_without_bom(&le), Some("utf-32le"));
+    assert_eq!(identify_utf32_without_bom(&be), Some("utf-32be"));
+
+    let utf16 = encode(text, "utf-16le", EncoderTrap::Strict).unwrap();
     let tests = [
-        ("iso-8859-14", "windows-1254", true),
-        ("iso-8859-14", "euc-kr", false),
+        // NUL bytes only (fit both byte orders)
+        [0u8; 16].as_slice(),
+        // small integers
+        b"\x01\x00\x00\x00\x02\x00\x00\x00\x03\x00\x00\x00\x04\x00\x00\x00".as_slice(),
+        // utf-16
+        utf16.as_slice(),
+        // single-byte text
+        b"Bonjour, voici le r\xe9sum\xe9".as_slice(),
+    ];
+    for test in tests {
+        assert_eq!(identify_utf32_without_bom(test), None, "{:?}", test);
+    }
+}
+
+#[test]
+fn test_identify_utf8_variants() {
+    let tests = [
+        (
+            b"Java \xed\xa0\xbd\xed\xb8\x80".as_slice(),
+            vec!["cesu-8", "mutf-8"],
+        ),
+        (b"Java \xc0\x80".as_slice(), vec!["mutf-8"]),
+        (b"file\xed\xb0\x80.txt".as_slice(), vec!["wtf-8"]),
+        ("Emoji 😀 in UTF-8".as_bytes(), vec![]),
+    ];
+    for (sequence, expected) in tests {
+        assert_eq!(identify_utf8_variants(sequence), expected, "{:?}", sequence);
+    }
+}
+
+#[test]
+fn test_iana_name() {
+    let tests = [
+        ("utf8", Some("utf-8")),
+        ("csibm866", Some("ibm866")),
+        ("whatever", None),
+        ("korean", Some("euc-kr")),
     ];
     for test in &tests {
-        assert_eq!(is_cp_similar(test.0, test.1), test.2);
+        assert_eq!(iana_name(test.0), test.1);
     }
 }
 
@@ -111,7 +187,10 @@ fn test_is_cp_similar() {
 fn test_any_specified_encoding() {
     let tests =     [
         (b"<head><meta charset=\"utf8\"".as_slice(), Some("utf-8".to_string())),
-        (b"<head coding='korean'> blah".as_slice(), Some("euc-kr".to_string())),
+        // loose "coding" / "encoding" words are not declarations
+        (b"<head coding='korean'> blah".as_slice(), None),
+        (b"Content-Transfer-Encoding: base64\r\nencoding: latin1".as_slice(), None),
+        (b"# -*- coding: korean -*-".as_slice(), Some("euc-kr".to_string())),
         (
             b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x01,\x00\x00\x00\xaf\x08\x06\x00\x00\x00G.\xe3\xb7\x00\x00\x1c\xfdIDATx".as_slice(),
             None,
@@ -136,6 +215,7 @@ fn test_cp_similarity() {
         ("iso-8859-14", "windows-1254", 0.75, 1.0), // high similarity
         ("windows-1250", "windows-1253", 0.5, 0.75), // low similarity
         ("iso-8859-14", "euc-kr", 0.0, 0.0),        // eur-kr is multi-byte
+        ("ibm437", "ibm850", 0.8, 1.0),             // built-in DOS code pages
     ];
     for test in &tests {
         let sim = cp_similarity(test.0, test.1);
@@ -238,3 +318,262 @@ fn test_decode_wrong_chunks() {
         }
     }
 }
+
+#[test]
+fn test_decode_encode_errors() {
+    assert!(matches!(
+        decode(b"abc", "utf-57", DecoderTrap::Strict, false, false),
+        Err(Error::UnknownEncoding(name)) if name == "utf-57"
+    ));
+    assert!(matches!(
+        decode(b"abc\xff\xfedef", "utf-8", DecoderTrap::Strict, false, false),
+        Err(Error::Decode { encoding, offset: 3 }) if encoding == "utf-8"
+    ));
+    assert!(matches!(
+        encode("abc", "utf-57", EncoderTrap::Strict),
+        Err(Error::UnknownEncoding(_))
+    ));
+    assert!(matches!(
+        encode("Тест", "windows-1252", EncoderTrap::Strict),
+        Err(Error::Encode { encoding }) if encoding == "windows-1252"
+    ));
+}
+
+#[test]
+fn test_decode_traps() {
+    // both decoding backends (`encoding` and `encoding_rs`) have to behave the same way
+    assert!(matches!(
+        decode(
+            b"ab\xa4\xa2\x80cd",
+            "euc-jp",
+            DecoderTrap::Strict,
+            false,
+            false
+        ),
+        Err(Error::Decode { offset: 4, .. })
+    ));
+    assert_eq!(
+        decode(
+            b"ab\xa4\xa2\x80cd",
+            "euc-jp",
+            DecoderTrap::Replace,
+            false,
+            false
+        )
+        .unwrap(),
+        "abあ\u{FFFD}cd"
+    );
+    assert_eq!(
+        decode(
+            b"ab\xa4\xa2\x80cd",
+            "euc-jp",
+            DecoderTrap::Ignore,
+            false,
+            false
+        )
+        .unwrap(),
+        "abあcd"
+    );
+    // incomplete sequence at the end is ignored only if asked to
+    assert!(decode(
+        b"\xe3\x81\x82\xe3\x81",
+        "utf-8",
+        DecoderTrap::Strict,
+        false,
+        false
+    )
+    .is_err());
+    assert_eq!(
+        decode(
+            b"\xe3\x81\x82\xe3\x81",
+            "utf-8",
+            DecoderTrap::Strict,
+            false,
+            true
+        )
+        .unwrap(),
+        "あ"
+    );
+    assert_eq!(
+        decode(
+            b"\x81\x40\x82\xa0",
+            "shift_jis",
+            DecoderTrap::Strict,
+            false,
+            false
+        )
+        .unwrap(),
+        "\u{3000}\u{3042}"
+    );
+}
+
+#[test]
+fn test_sampling_offsets() {
+    let tests = [
+        (
+            SamplingStrategy::EvenlySpaced,
+            0,
+            vec![0, 200, 400, 600, 800],
+        ),
+        (
+            SamplingStrategy::EvenlySpaced,
+            100,
+            vec![100, 300, 500, 700, 900],
+        ),
+        (SamplingStrategy::NonAsciiDense, 0, vec![0, 445, 900]),
+        (SamplingStrategy::HeadTail, 0, vec![0, 100, 200, 800, 900]),
+        (SamplingStrategy::HeadTail, 950, vec![950]),
+    ];
+    for (strategy, start, expected) in tests {
+        let offsets = sampling_offsets(strategy, start, 1000, 5, 100, || {
+            vec![10, 11, 12, 495, 496, 990]
+        });
+        assert_eq!(offsets, expected, "{:?} from {}", strategy, start);
+    }
+
+    // random offsets are reproducible and fit sequence
+    let random = |seed| {
+        sampling_offsets(
+            SamplingStrategy::Random { seed },
+            10,
+            1000,
+            5,
+            100,
+            Vec::new,
+        )
+    };
+    assert_eq!(random(7), random(7));
+    assert_ne!(random(7), random(8));
+    assert!(random(7).iter().all(|&offset| (10..=900).contains(&offset)));
+
+    // no non-ascii units at all: fallback to evenly spaced
+    assert_eq!(
+        sampling_offsets(SamplingStrategy::NonAsciiDense, 0, 1000, 5, 100, Vec::new),
+        vec![0, 200, 400, 600, 800]
+    );
+}
+
+#[test]
+fn test_encoding_hints_parsing() {
+    let tests = [
+        ("text/html; charset=windows-1251", Some("windows-1251")),
+        ("text/plain;Charset=\"KOI8-R\"", Some("koi8-r")),
+        (
+            "text/plain; format=flowed; charset='latin1'",
+            Some("windows-1252"),
+        ),
+        ("text/plain; charset=utf-57", None),
+        ("text/plain", None),
+        ("charset=utf-8", None),
+    ];
+    for (content_type, expected) in tests {
+        assert_eq!(
+            content_type_charset(content_type),
+            expected,
+            "{}",
+            content_type
+        );
+    }
+
+    let tests = [
+        ("ru_RU", vec!["windows-1251", "koi8-r", "ibm866"]),
+        (
+            "ru-RU.KOI8-R",
+            vec!["koi8-r", "windows-1251", "koi8-r", "ibm866"],
+        ),
+        ("zh_TW", vec!["big5"]),
+        ("zh_SG.GBK", vec!["gbk", "gbk", "gb18030", "big5"]),
+        ("de_DE@euro", vec!["windows-1252", "iso-8859-15"]),
+        ("C", vec![]),
+    ];
+    for (locale, expected) in tests {
+        assert_eq!(locale_encodings(locale), expected, "{}", locale);
+    }
+
+    let tests = [
+        ("jp", vec!["shift_jis", "euc-jp", "iso-2022-jp"]),
+        (".TW", vec!["big5"]),
+        ("news.example.com.ua.", vec!["windows-1251", "koi8-u"]),
+        ("com", vec![]),
+    ];
+    for (tld, expected) in tests {
+        assert_eq!(tld_encodings(tld), expected, "{}", tld);
+    }
+
+    // every hinted encoding is supported
+    for encodings in LOCALE_ENCODINGS.values() {
+        for encoding in encodings {
+            assert!(IANA_SUPPORTED.contains(encoding), "{}", encoding);
+        }
+    }
+    for locale in TLD_LOCALES.values() {
+        assert!(LOCALE_ENCODINGS.contains_key(locale), "{}", locale);
+    }
+}
+
+#[test]
+fn test_blank_box_drawing() {
+    let tests = [
+        ("┌──┐\n│ab│\n└──┘", "    \n│ab│\n    "),
+        ("░▒▓ SYSOP ▓▒░", "    SYSOP    "),
+        ("αΓ╗╖Σ", "αΓ╗╖Σ"),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(blank_box_drawing(input), expected);
+    }
+    assert!(is_oem_code_page("ibm850"));
+    assert!(!is_oem_code_page("ibm866"));
+}
+
+#[test]
+fn test_identify_ebcdic() {
+    let text = "ACCOUNT   NAME          BALANCE\n0001234   JOHN SMITH    1,234.56\n";
+    let ebcdic = encode(text, "ibm037", EncoderTrap::Strict).unwrap();
+    assert!(identify_ebcdic(&ebcdic));
+    assert!(!identify_ebcdic(text.as_bytes()));
+    assert!(!identify_ebcdic(
+        &encode("Größe der Datei", "windows-1252", EncoderTrap::Strict).unwrap()
+    ));
+    // packed decimal fields are binary
+    assert!(!identify_ebcdic(
+        b"\xc1\x40\x00\x01\x02\x0c\x00\x00\x1c\x40\xc2\x00\x03\x4c"
+    ));
+    assert!(is_ebcdic_encoding("ibm01140"));
+}
+
+#[test]
+fn test_identify_oem() {
+    let text = "Größe der Datei: 120 KB";
+    assert!(identify_oem(
+        &encode(text, "ibm437", EncoderTrap::Strict).unwrap()
+    ));
+    assert!(identify_oem(
+        &encode("╔══════╗ BBS", "ibm437", EncoderTrap::Strict).unwrap()
+    ));
+    assert!(!identify_oem(b"plain ascii text"));
+    assert!(!identify_oem(
+        &encode(text, "windows-1252", EncoderTrap::Strict).unwrap()
+    ));
+    assert!(!identify_oem(
+        &encode("Łódź i Kraków", "iso-8859-2", EncoderTrap::Strict).unwrap()
+    ));
+
+    // continuation bytes of UTF-8 and punctuation of windows-1252 are in 0x80..=0x9F as well
+    let texts = [
+        "“Smart quotes” cost €5 – it’s a bargain… ™",
+        "Größe der Datei: 120 KB, Preis: 9,99 € — „günstig“",
+        "Всеки човек има право на образование. Ё Я Ю Э",
+        "我没有埋怨，磋砣的只是一些时间。",
+    ];
+    for text in texts {
+        assert!(!identify_oem(text.as_bytes()), "{}", text);
+    }
+    for text in &texts[..2] {
+        let windows_1252 = encode(text, "windows-1252", EncoderTrap::Strict).unwrap();
+        assert!(!identify_oem(&windows_1252), "{}", text);
+    }
+    // UTF-8 cut in the middle of a sequence
+    let utf8 = format!("Datei{}", "Größe ".repeat(OEM_SNIFF_SIZE));
+    assert!(std::str::from_utf8(&utf8.as_bytes()[..OEM_SNIFF_SIZE]).is_err());
+    assert!(!identify_oem(utf8.as_bytes()));
+}
@@ -1,19 +1,27 @@
 #![allow(dead_code)]
 
 use crate::assets::LANGUAGES;
+use crate::codecs::{codec_from_label, is_registered_multibyte};
 use crate::consts::{
-    ENCODING_MARKS, IANA_SUPPORTED, IANA_SUPPORTED_SIMILAR, RE_POSSIBLE_ENCODING_INDICATION,
-    UNICODE_RANGES_COMBINED, UNICODE_SECONDARY_RANGE_KEYWORD,
+    EBCDIC_SNIFF_SIZE, ENCODING_MARKS, IANA_SUPPORTED, LOCALE_ENCODINGS, MAX_PROCESSED_BYTES,
+    OEM_SNIFF_SIZE, TLD_LOCALES, UNICODE_RANGES_COMBINED, UNICODE_SECONDARY_RANGE_KEYWORD,
+    UTF16_SNIFF_SIZE,
 };
-use crate::entity::Language;
+use crate::declarations::{find_declarations, unambiguous_encoding};
+use crate::entity::{Language, MessDetectorSettings, SamplingStrategy};
+use crate::error::Error;
+use crate::md::configured_mess_ratio;
+use crate::report::Utf7Verdict;
 
 use ahash::{HashSet, HashSetExt};
-use encoding::label::encoding_from_whatwg_label;
-use encoding::{CodecError, DecoderTrap, EncoderTrap, Encoding, EncodingRef, StringWriter};
+use encoding::{DecoderTrap, EncoderTrap, Encoding, EncodingRef, StringWriter};
 use icu_normalizer::DecomposingNormalizer;
+use ordered_float::OrderedFloat;
 use unicode_names2::name;
 
 use std::borrow::Cow;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::RangeInclusive;
 #[cfg(any(test, feature = "performance"))]
 use std::path::{Path, PathBuf};
 
@@ -90,6 +98,12 @@ pub fn is_multi_byte_encoding(name: &str) -> bool {
         "utf-8",
         "utf-16le",
         "utf-16be",
+        "utf-32le",
+        "utf-32be",
+        "utf-7",
+        "cesu-8",
+        "mutf-8",
+        "wtf-8",
         "euc-jp",
         "euc-kr",
         "iso-2022-jp",
@@ -100,16 +114,294 @@ pub fn is_multi_byte_encoding(name: &str) -> bool {
         "shift_jis",
     ]
     .contains(&name)
+        || is_registered_multibyte(name)
 }
 
-// Try to detect multibyte encoding by signature
-pub(crate) fn identify_sig_or_bom(sequence: &[u8]) -> (Option<String>, Option<&[u8]>) {
-    ENCODING_MARKS
+// Verify if encoding is one of built-in DOS (OEM) code pages, where box drawing and
+// block elements are regular characters of text user interfaces and ASCII art
+pub(crate) fn is_oem_code_page(name: &str) -> bool {
+    ["ibm437", "ibm737", "ibm775", "ibm850", "ibm852", "ibm865"].contains(&name)
+}
+
+// Verify if encoding is one of built-in EBCDIC code pages
+pub(crate) fn is_ebcdic_encoding(name: &str) -> bool {
+    ["ibm037", "ibm500", "ibm1047", "ibm01140"].contains(&name)
+}
+
+// Replace box drawing and block elements by spaces, so frames and ASCII art do not count as chaos.
+// Ones touching a letter are kept: text decoded with a wrong code page mixes them with letters.
+pub(crate) fn blank_box_drawing(text: &str) -> String {
+    let is_box = |ch: char| matches!(ch, '\u{2500}'..='\u{259F}');
+    let chars: Vec<char> = text.chars().collect();
+    chars
         .iter()
-        .find(|&(_, enc_sig)| sequence.starts_with(enc_sig))
-        .map_or((None, None), |(enc_name, enc_sig)| {
-            (Some((*enc_name).to_string()), Some(*enc_sig))
+        .enumerate()
+        .map(|(idx, &ch)| {
+            let touches_letter = [idx.checked_sub(1), Some(idx + 1)]
+                .iter()
+                .filter_map(|&idx| chars.get(idx?))
+                .any(|ch| ch.is_alphabetic());
+            match is_box(ch) && !touches_letter {
+                true => ' ',
+                false => ch,
+            }
         })
+        .collect()
+}
+
+// Try to detect multibyte encoding by signature.
+// The longest one wins, as utf-32le BOM starts with utf-16le one. Yet FF FE 00 00 may also be
+// utf-16le BOM followed by U+0000: it is taken for utf-32le, unless 4-bytes units after it
+// (in the sniffed part) are not valid utf-32le code points, then it is utf-16le.
+pub(crate) fn identify_sig_or_bom(sequence: &[u8]) -> (Option<String>, Option<&[u8]>) {
+    let mark = ENCODING_MARKS
+        .iter()
+        .filter(|&(_, enc_sig)| sequence.starts_with(enc_sig))
+        .max_by_key(|&(_, enc_sig)| enc_sig.len());
+    let mark = match mark {
+        Some((&"utf-32le", enc_sig)) => {
+            let units = &sequence[enc_sig.len()..sequence.len().min(UTF16_SNIFF_SIZE)];
+            match units.chunks_exact(4).all(|bytes| {
+                char::from_u32(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    .is_some()
+            }) {
+                true => mark,
+                false => ENCODING_MARKS.get_key_value("utf-16le"),
+            }
+        }
+        _ => mark,
+    };
+    mark.map_or((None, None), |(enc_name, enc_sig)| {
+        (Some((*enc_name).to_string()), Some(*enc_sig))
+    })
+}
+
+// Guess endianness of UTF-16 without BOM by parity of NUL bytes.
+// Most of texts contain ASCII characters (spaces, digits, punctuation), which high byte is NUL,
+// while low bytes are hardly ever NUL. Sample must also consist of valid surrogate pairs and
+// nearly without control characters, otherwise it is rather a binary file.
+pub(crate) fn identify_utf16_without_bom(sequence: &[u8]) -> Option<&'static str> {
+    let sample = &sequence[..sequence.len().min(UTF16_SNIFF_SIZE) & !1];
+    let units_count = sample.len() / 2;
+    if units_count < 2 {
+        return None;
+    }
+    let nul_count = |parity: usize| {
+        sample
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even_nul_count, odd_nul_count) = (nul_count(0), nul_count(1));
+    let (encoding, high_nul_count, low_nul_count) = match odd_nul_count.cmp(&even_nul_count) {
+        std::cmp::Ordering::Greater => ("utf-16le", odd_nul_count, even_nul_count),
+        std::cmp::Ordering::Less => ("utf-16be", even_nul_count, odd_nul_count),
+        std::cmp::Ordering::Equal => return None,
+    };
+    // at least 1% of high NUL bytes and no more than 5% of low ones
+    if high_nul_count * 100 < units_count || low_nul_count * 20 > units_count {
+        return None;
+    }
+
+    let units = sample.chunks_exact(2).map(|pair| match encoding {
+        "utf-16le" => u16::from_le_bytes([pair[0], pair[1]]),
+        _ => u16::from_be_bytes([pair[0], pair[1]]),
+    });
+    let mut control_count: usize = 0;
+    let mut decoded = char::decode_utf16(units).peekable();
+    while let Some(character) = decoded.next() {
+        match character {
+            Ok(c) if c.is_control() && !c.is_ascii_whitespace() => control_count += 1,
+            Ok(_) => {}
+            // high surrogate may be cut off by the end of sample
+            Err(err)
+                if decoded.peek().is_none()
+                    && sample.len() < sequence.len()
+                    && (0xD800..0xDC00).contains(&err.unpaired_surrogate()) => {}
+            Err(_) => return None,
+        }
+    }
+    (control_count * 100 <= units_count).then_some(encoding)
+}
+
+// Guess endianness of UTF-32 without BOM: every 4-bytes unit of sample must be a valid
+// code point (up to U+10FFFF, not a surrogate) in one byte order only. Control characters
+// are tolerated as rarely as for UTF-16, so arrays of small integers do not pass.
+pub(crate) fn identify_utf32_without_bom(sequence: &[u8]) -> Option<&'static str> {
+    let sample = &sequence[..sequence.len().min(UTF16_SNIFF_SIZE) & !3];
+    let units_count = sample.len() / 4;
+    if units_count < 2 {
+        return None;
+    }
+    let chars = |unit: fn([u8; 4]) -> u32| -> Option<Vec<char>> {
+        sample
+            .chunks_exact(4)
+            .map(|bytes| char::from_u32(unit([bytes[0], bytes[1], bytes[2], bytes[3]])))
+            .collect()
+    };
+    let (encoding, chars) = match (chars(u32::from_le_bytes), chars(u32::from_be_bytes)) {
+        (Some(chars), None) => ("utf-32le", chars),
+        (None, Some(chars)) => ("utf-32be", chars),
+        _ => return None,
+    };
+    let control_count = chars
+        .iter()
+        .filter(|c| c.is_control() && !c.is_ascii_whitespace())
+        .count();
+    (control_count * 100 <= units_count).then_some(encoding)
+}
+
+// Check if byte distribution of sample looks like EBCDIC text: space (0x40) is frequent,
+// most of bytes are letters (0x81..=0xE9) or digits (0xF0..=0xF9), and control bytes other
+// than tab, carriage return and newlines (NEL 0x15, LF 0x25) are rare.
+// ASCII texts do not pass, as their letters are punctuation in EBCDIC.
+pub(crate) fn identify_ebcdic(sequence: &[u8]) -> bool {
+    let sample = &sequence[..sequence.len().min(EBCDIC_SNIFF_SIZE)];
+    let count = |f: fn(u8) -> bool| sample.iter().filter(|&&b| f(b)).count();
+    let space_count = count(|b| b == 0x40);
+    let alphanumeric_count = count(
+        |b| matches!(b, 0x81..=0x89 | 0x91..=0x99 | 0xA2..=0xA9 | 0xC1..=0xC9 | 0xD1..=0xD9 | 0xE2..=0xE9 | 0xF0..=0xF9),
+    );
+    let control_count = count(|b| b < 0x40 && !matches!(b, 0x05 | 0x0D | 0x15 | 0x25));
+    // at least 5% of spaces, 60% of spaces & alphanumerics and no more than 1% of controls
+    !sample.is_empty()
+        && space_count * 20 >= sample.len()
+        && (space_count + alphanumeric_count) * 10 >= sample.len() * 6
+        && control_count * 100 <= sample.len()
+}
+
+// Check if sample may be DOS text: frames of text user interfaces repeat box drawing
+// characters (0xB0..=0xDF), which are letters elsewhere, and letters of all DOS (OEM) code pages
+// are mostly placed in 0x80..=0x9F, inside words. The same bytes are punctuation of windows
+// code pages (quotes, dashes, €), which is rather found next to spaces and digits, and
+// continuation bytes of UTF-8, so valid UTF-8 and most of ISO-8859 or windows texts do not pass.
+pub(crate) fn identify_oem(sequence: &[u8]) -> bool {
+    let sample = &sequence[..sequence.len().min(OEM_SNIFF_SIZE)];
+    // sample of valid UTF-8 may be cut in the middle of a sequence
+    let is_utf8 = match std::str::from_utf8(sample) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    };
+    if is_utf8 {
+        return false;
+    }
+    if sample
+        .windows(3)
+        .any(|w| (0xB0..=0xDF).contains(&w[0]) && w[0] == w[1] && w[1] == w[2])
+    {
+        return true;
+    }
+
+    // ASCII letters and DOS letters: 0x80..=0x9A, á í ó ú ñ Ñ (0xA0..=0xA5) and ß (0xE1)
+    let is_letter =
+        |b: u8| b.is_ascii_alphabetic() || matches!(b, 0x80..=0x9A | 0xA0..=0xA5 | 0xE1);
+    let (mut letters, mut letters_in_words) = (0, 0);
+    for (pos, byte) in sample.iter().enumerate() {
+        if !(0x80..=0x9F).contains(byte) {
+            continue;
+        }
+        letters += 1;
+        // right single quotation mark of windows code pages is an apostrophe inside words
+        // (f.e. "it’s"), while Æ is rarely found there
+        if *byte != 0x92
+            && pos > 0
+            && is_letter(sample[pos - 1])
+            && sample.get(pos + 1).is_some_and(|&b| is_letter(b))
+        {
+            letters_in_words += 1;
+        }
+    }
+    letters_in_words > 0 && letters_in_words * 2 >= letters
+}
+
+// UTF-8 variants which byte patterns are found in sequence: surrogates written as 3-bytes
+// sequences (paired ones for cesu-8 & mutf-8, lone ones for wtf-8) or overlong NUL (mutf-8)
+pub(crate) fn identify_utf8_variants(sequence: &[u8]) -> Vec<&'static str> {
+    let is_surrogate = |pos: usize, second: RangeInclusive<u8>| {
+        sequence.get(pos) == Some(&0xed)
+            && sequence.get(pos + 1).is_some_and(|b| second.contains(b))
+            && sequence
+                .get(pos + 2)
+                .is_some_and(|b| (0x80..=0xbf).contains(b))
+    };
+    let (mut has_paired, mut has_lone, mut has_overlong_nul) = (false, false, false);
+    let mut pos = 0;
+    while pos < sequence.len() {
+        if is_surrogate(pos, 0xa0..=0xaf) && is_surrogate(pos + 3, 0xb0..=0xbf) {
+            has_paired = true;
+            pos += 6;
+            continue;
+        }
+        has_lone |= is_surrogate(pos, 0xa0..=0xbf);
+        has_overlong_nul |= sequence[pos] == 0xc0 && sequence.get(pos + 1) == Some(&0x80);
+        pos += 1;
+    }
+    [
+        ("cesu-8", has_paired),
+        ("mutf-8", has_paired || has_overlong_nul),
+        ("wtf-8", has_lone),
+    ]
+    .into_iter()
+    .filter(|(_, found)| *found)
+    .map(|(encoding, _)| encoding)
+    .collect()
+}
+
+// Decide whether 7-bit content is utf-7 rather than ascii: it must have shift sequences
+// ("+...-"), and decoded text must be plausible. None if content is not 7-bit at all.
+pub(crate) fn utf7_verdict(
+    sequence: &[u8],
+    threshold: OrderedFloat<f32>,
+    mess_detector: &MessDetectorSettings,
+) -> Option<Utf7Verdict> {
+    if !sequence.is_ascii() {
+        return None;
+    }
+    let mut shift_sequences: usize = 0;
+    let mut pos = 0;
+    while let Some(start) = find_subsequence(sequence, pos, b"+") {
+        pos = start + 1;
+        // "+-" is "+" itself
+        if sequence.get(pos) != Some(&b'-') {
+            shift_sequences += 1;
+        }
+        while sequence
+            .get(pos)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'+' || *b == b'/')
+        {
+            pos += 1;
+        }
+    }
+    if shift_sequences == 0 {
+        return Some(Utf7Verdict::NoShiftSequence);
+    }
+
+    // large content is sampled up to whitespace, which cannot be inside of shift sequence
+    let mut sample = sequence;
+    if sample.len() > MAX_PROCESSED_BYTES {
+        sample = &sample[..MAX_PROCESSED_BYTES];
+        sample = &sample[..sample
+            .iter()
+            .rposition(|b| b.is_ascii_whitespace())
+            .unwrap_or_default()];
+    }
+    let decoded = match decode(sample, "utf-7", DecoderTrap::Strict, false, false) {
+        Ok(decoded) => decoded,
+        Err(Error::Decode { offset, .. }) => {
+            return Some(Utf7Verdict::InvalidShiftSequence { offset })
+        }
+        Err(_) => return None,
+    };
+    let (mess_ratio, _) = configured_mess_ratio(decoded, threshold, mess_detector);
+    Some(match mess_ratio < *threshold {
+        true => Utf7Verdict::Plausible {
+            shift_sequences,
+            mess_ratio,
+        },
+        false => Utf7Verdict::Implausible { mess_ratio },
+    })
 }
 
 // Try to get standard name by alternative labels
@@ -119,30 +411,72 @@ pub fn iana_name(cp_name: &str) -> Option<&str> {
         .then_some(cp_name)
         .or_else(|| {
             // if not found, try to use alternative way
-            encoding_from_whatwg_label(cp_name).map(|enc| enc.whatwg_name().unwrap_or(enc.name()))
+            codec_from_label(cp_name).map(|enc| enc.whatwg_name().unwrap_or(enc.name()))
         })
 }
 
-pub(crate) fn is_cp_similar(iana_name_a: &str, iana_name_b: &str) -> bool {
-    IANA_SUPPORTED_SIMILAR.contains_key(iana_name_a)
-        && IANA_SUPPORTED_SIMILAR[iana_name_a].contains(&iana_name_b)
+// Position of the first occurrence of needle at or after from
+pub(crate) fn find_subsequence(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|idx| from + idx)
 }
 
-// Extract using ASCII-only decoder any specified encoding in the first n-bytes.
+// Encoding unambiguously declared in the first n-bytes (if all such declarations agree)
 pub(crate) fn any_specified_encoding(sequence: &[u8], search_zone: usize) -> Option<String> {
-    encoding::all::ASCII
-        .decode(
-            &sequence[0..search_zone.min(sequence.len())],
-            DecoderTrap::Ignore,
-        )
-        .ok()
-        .and_then(|test_string| {
-            RE_POSSIBLE_ENCODING_INDICATION
-                .captures_iter(&test_string)
-                .map(|c| c.extract())
-                .find_map(|(_, [specified_encoding])| iana_name(specified_encoding))
-                .map(|found_iana| found_iana.to_string())
+    unambiguous_encoding(&find_declarations(sequence, search_zone)).map(String::from)
+}
+
+// Charset parameter of Content-Type header (f.e. `text/html; charset="windows-1251"`)
+pub(crate) fn content_type_charset(content_type: &str) -> Option<&str> {
+    content_type
+        .split(';')
+        .skip(1)
+        .find_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            name.trim()
+                .eq_ignore_ascii_case("charset")
+                .then(|| value.trim().trim_matches(|c| c == '"' || c == '\''))
         })
+        .and_then(iana_name)
+}
+
+// Encodings hinted by locale (f.e. `ru_RU.KOI8-R`): its codeset first, then encodings
+// commonly used for its language & territory (or just language)
+pub(crate) fn locale_encodings(locale: &str) -> Vec<String> {
+    let locale = locale.trim().to_lowercase();
+    let locale = locale.split('@').next().unwrap_or_default();
+    let (language_territory, codeset) = match locale.split_once('.') {
+        Some((language_territory, codeset)) => (language_territory, Some(codeset)),
+        None => (locale, None),
+    };
+    let language_territory = language_territory.replace('-', "_");
+    let language = language_territory.split('_').next().unwrap_or_default();
+    codeset
+        .and_then(iana_name)
+        .map(String::from)
+        .into_iter()
+        .chain(
+            LOCALE_ENCODINGS
+                .get(language_territory.as_str())
+                .or_else(|| LOCALE_ENCODINGS.get(language))
+                .into_iter()
+                .flatten()
+                .map(|enc| enc.to_string()),
+        )
+        .collect()
+}
+
+// Encodings hinted by top-level domain (f.e. `jp`, `.jp` or `example.co.jp`)
+pub(crate) fn tld_encodings(tld: &str) -> Vec<String> {
+    let tld = tld.trim().trim_end_matches('.').to_lowercase();
+    tld.rsplit('.')
+        .next()
+        .and_then(|tld| TLD_LOCALES.get(tld))
+        .map(|locale| locale_encodings(locale))
+        .unwrap_or_default()
 }
 
 // Calculate similarity of two single byte encodings
@@ -152,22 +486,26 @@ pub(crate) fn cp_similarity(iana_name_a: &str, iana_name_b: &str) -> f32 {
         return 0.0;
     }
 
-    if let (Some(encoder_a), Some(encoder_b)) = (
-        encoding_from_whatwg_label(iana_name_a),
-        encoding_from_whatwg_label(iana_name_b),
-    ) {
-        let character_match_count = (1..255u8)
-            .filter(|&ch| {
-                let res_a = encoder_a.decode(&[ch], DecoderTrap::Ignore).ok();
-                let res_b = encoder_b.decode(&[ch], DecoderTrap::Ignore).ok();
-                res_a.is_some() && res_a == res_b //check that they aren't none and equal
-            })
-            .count();
-        return character_match_count as f32 / 254.0;
+    if let (Some(encoder_a), Some(encoder_b)) =
+        (codec_from_label(iana_name_a), codec_from_label(iana_name_b))
+    {
+        return codecs_similarity(encoder_a, encoder_b);
     }
     0.0 // Return 0.0 if encoders could not be retrieved.
 }
 
+// Share of bytes which two single byte codecs decode to the same characters
+pub(crate) fn codecs_similarity(encoder_a: &dyn Encoding, encoder_b: &dyn Encoding) -> f32 {
+    let character_match_count = (1..255u8)
+        .filter(|&ch| {
+            let res_a = encoder_a.decode(&[ch], DecoderTrap::Ignore).ok();
+            let res_b = encoder_b.decode(&[ch], DecoderTrap::Ignore).ok();
+            res_a.is_some() && res_a == res_b //check that they aren't none and equal
+        })
+        .count();
+    character_match_count as f32 / 254.0
+}
+
 // Test Decoding bytes to string with specified encoding without writing result to memory
 // returns true if everything is correctly decoded, otherwise false
 struct DecodeTestResult {
@@ -209,85 +547,233 @@ pub fn decode(
     how_process_errors: DecoderTrap,
     only_test: bool,
     is_chunk: bool,
-) -> Result<String, String> {
-    let encoder = encoding_from_whatwg_label(from_encoding)
-        .ok_or(format!("Encoding '{}' not found", from_encoding))?;
+) -> Result<String, Error> {
+    ResolvedCodec::resolve(from_encoding)?.decode(input, how_process_errors, only_test, is_chunk)
+}
 
-    let mut buf = DecodeTestResult {
-        only_test,
-        data: String::new(),
-    };
-    let mut err = CodecError {
-        upto: 0,
-        cause: Cow::from(String::new()),
-    };
-    let chunk_len = input.len();
-    let mut begin_offset: usize = 0;
-    let mut end_offset: usize = chunk_len;
-    let mut error_occured: bool;
-    loop {
-        let res = decode_to(
+// Codec looked up by encoding name once (Detector resolves codecs of all its encodings),
+// so that decoding (f.e. chunks of probed sequence) does not search the registry of codecs again
+#[derive(Clone, Copy)]
+pub(crate) struct ResolvedCodec<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) is_multi_byte: bool,
+    encoder: EncodingRef,
+    // decoder of encoding_rs which replaces the one of `encoding` crate for strict decoding
+    #[cfg(feature = "encoding_rs")]
+    encoding_rs: Option<&'static encoding_rs::Encoding>,
+}
+
+impl<'a> ResolvedCodec<'a> {
+    pub(crate) fn resolve(name: &'a str) -> Result<Self, Error> {
+        let encoder =
+            codec_from_label(name).ok_or_else(|| Error::UnknownEncoding(name.to_string()))?;
+        Ok(ResolvedCodec {
+            name,
+            is_multi_byte: is_multi_byte_encoding(name),
             encoder,
-            &input[begin_offset..end_offset],
-            how_process_errors,
-            &mut buf,
-        );
-        error_occured = res.is_err();
-        if let DecoderTrap::Strict = how_process_errors {
-            if !is_chunk || res.is_ok() || !is_multi_byte_encoding(from_encoding) {
-                break;
+            #[cfg(feature = "encoding_rs")]
+            encoding_rs: encoding_rs_counterpart(encoder),
+        })
+    }
+
+    // Same codec with decoding backend of WHATWG encodings chosen explicitly:
+    // encoding_rs (default) or `encoding` crate
+    #[cfg(feature = "encoding_rs")]
+    pub(crate) fn with_encoding_rs(mut self, enabled: bool) -> Self {
+        self.encoding_rs = encoding_rs_counterpart(self.encoder).filter(|_| enabled);
+        self
+    }
+
+    // Same as decode() function, but with already resolved codec
+    pub(crate) fn decode(
+        &self,
+        input: &[u8],
+        how_process_errors: DecoderTrap,
+        only_test: bool,
+        is_chunk: bool,
+    ) -> Result<String, Error> {
+        let mut buf = DecodeTestResult {
+            only_test,
+            data: String::new(),
+        };
+        let chunk_len = input.len();
+        let mut begin_offset: usize = 0;
+        let mut end_offset: usize = chunk_len;
+        let failure = loop {
+            // drop output of the previous (failed) attempt
+            buf.data.clear();
+            let Err(failure) = self.decode_to(
+                &input[begin_offset..end_offset],
+                how_process_errors,
+                &mut buf,
+            ) else {
+                break None;
+            };
+            let failure = DecodeFailure {
+                offset: failure.offset + begin_offset,
+                ..failure
+            };
+            if !matches!(how_process_errors, DecoderTrap::Strict)
+                || !is_chunk
+                || !self.is_multi_byte
+            {
+                break Some(failure);
             }
-            err = res.unwrap_err();
-            if err.cause.contains("invalid sequence") {
+            if failure.cause.contains("invalid sequence") {
                 begin_offset += 1;
-            } else if err.cause.contains("incomplete sequence") {
+            } else if failure.cause.contains("incomplete sequence") {
                 end_offset -= 1;
             }
             if end_offset - begin_offset < 1 || begin_offset > 3 || (chunk_len - end_offset) > 3 {
-                break;
+                break Some(failure);
+            }
+        };
+        if let Some(failure) = failure {
+            return Err(Error::Decode {
+                encoding: self.name.to_string(),
+                offset: failure.offset,
+            });
+        }
+        Ok(String::from(buf.get_buffer()))
+    }
+
+    // Length of the beginning of bytes which fits the encoding, the rest (if any) is an incomplete
+    // sequence which may be completed by next bytes. Invalid sequence is an error.
+    pub(crate) fn decodable_prefix_len(&self, input: &[u8]) -> Result<usize, Error> {
+        let mut buf = DecodeTestResult {
+            only_test: true,
+            data: String::new(),
+        };
+        match self.decode_to(input, DecoderTrap::Strict, &mut buf) {
+            Ok(()) => Ok(input.len()),
+            Err(failure) if failure.cause.contains("incomplete sequence") => Ok(failure.offset),
+            Err(failure) => Err(Error::Decode {
+                encoding: self.name.to_string(),
+                offset: failure.offset,
+            }),
+        }
+    }
+
+    // Copied implementation of decode_to from encoder lib
+    // (we need index of problematic chars & hacks for chunks)
+    fn decode_to(
+        &self,
+        input: &[u8],
+        trap: DecoderTrap,
+        ret: &mut dyn StringWriter,
+    ) -> Result<(), DecodeFailure> {
+        #[cfg(feature = "encoding_rs")]
+        if let Some(encoding) = self
+            .encoding_rs
+            .filter(|_| matches!(trap, DecoderTrap::Strict))
+        {
+            return decode_to_with_encoding_rs(encoding, input, ret);
+        }
+
+        let mut decoder = self.encoder.raw_decoder();
+        let mut remaining = 0;
+        loop {
+            let (offset, err) = decoder.raw_feed(&input[remaining..], ret);
+            let unprocessed = remaining + offset;
+
+            match err {
+                Some(err) => {
+                    remaining = remaining.wrapping_add_signed(err.upto);
+                    if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
+                        return Err(DecodeFailure {
+                            offset: unprocessed,
+                            cause: err.cause,
+                        });
+                    }
+                }
+                None => {
+                    remaining = input.len();
+                    if let Some(err) = decoder.raw_finish(ret) {
+                        remaining = remaining.wrapping_add_signed(err.upto);
+                        if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
+                            return Err(DecodeFailure {
+                                offset: unprocessed,
+                                cause: err.cause,
+                            });
+                        }
+                    }
+                    if remaining >= input.len() {
+                        return Ok(());
+                    }
+                }
             }
-        } else {
-            break;
         }
     }
-    if error_occured {
-        return Err(format!("{} at index {}", err.cause, err.upto));
+}
+
+impl Debug for ResolvedCodec<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResolvedCodec")
+            .field("name", &self.name)
+            .field("is_multi_byte", &self.is_multi_byte)
+            .finish()
     }
-    Ok(String::from(buf.get_buffer()))
 }
 
-// Copied implementation of decode_to from encoder lib
-// (we need index of problematic chars & hacks for chunks)
-fn decode_to(
-    encoder: EncodingRef,
+// Decoding failure details: offset of the first problematic byte and its cause
+struct DecodeFailure {
+    offset: usize,
+    cause: Cow<'static, str>,
+}
+
+// Encoding of encoding_rs which decodes bytes the same way as WHATWG encoding of `encoding`
+// crate. Others (ascii, iso-8859-1, hz, built-in and registered codecs) have no counterpart,
+// as well as ones which tables differ (newer WHATWG mappings of koi8-u, windows-1255, gbk,
+// gb18030 and iso-2022-jp). Error recovery differs too, so only strict decoding (the one
+// of detection) is done with encoding_rs.
+#[cfg(feature = "encoding_rs")]
+fn encoding_rs_counterpart(encoder: EncodingRef) -> Option<&'static encoding_rs::Encoding> {
+    let whatwg_name = encoder.whatwg_name()?;
+    if ["koi8-u", "windows-1255", "gbk", "gb18030", "iso-2022-jp"].contains(&whatwg_name) {
+        return None;
+    }
+    encoding_rs::Encoding::for_label_no_replacement(whatwg_name.as_bytes())
+        .filter(|encoding| encoding.name().eq_ignore_ascii_case(whatwg_name))
+}
+
+// Same thing as decode_to with strict trap, but decoder of encoding_rs is used. Invalid
+// sequence at the end of input is reported as incomplete one, as decode_to does.
+#[cfg(feature = "encoding_rs")]
+fn decode_to_with_encoding_rs(
+    encoding: &'static encoding_rs::Encoding,
     input: &[u8],
-    trap: DecoderTrap,
     ret: &mut dyn StringWriter,
-) -> Result<(), CodecError> {
-    let mut decoder = encoder.raw_decoder();
+) -> Result<(), DecodeFailure> {
+    use encoding_rs::DecoderResult;
+
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut buffer = String::with_capacity(input.len().clamp(16, 8192) * 3);
     let mut remaining = 0;
+    let mut last = false;
     loop {
-        let (offset, err) = decoder.raw_feed(&input[remaining..], ret);
-        let unprocessed = remaining + offset;
-
-        match err {
-            Some(err) => {
-                remaining = remaining.wrapping_add_signed(err.upto);
-                if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
-                    return Err(err);
-                }
+        let (result, read) =
+            decoder.decode_to_string_without_replacement(&input[remaining..], &mut buffer, last);
+        remaining += read;
+        match result {
+            DecoderResult::InputEmpty if last => {
+                ret.write_str(&buffer);
+                return Ok(());
             }
-            None => {
-                remaining = input.len();
-                if let Some(err) = decoder.raw_finish(ret) {
-                    remaining = remaining.wrapping_add_signed(err.upto);
-                    if !trap.trap(&mut *decoder, &input[unprocessed..remaining], ret) {
-                        return Err(err);
-                    }
-                }
-                if remaining >= input.len() {
-                    return Ok(());
-                }
+            // bytes of incomplete sequence may be pending, decoder has to be flushed
+            DecoderResult::InputEmpty => last = true,
+            DecoderResult::OutputFull => {
+                ret.write_str(&buffer);
+                buffer.clear();
+            }
+            DecoderResult::Malformed(bad_len, consumed_len) => {
+                ret.write_str(&buffer);
+                return Err(DecodeFailure {
+                    offset: remaining - bad_len as usize - consumed_len as usize,
+                    cause: match last {
+                        true => "incomplete sequence".into(),
+                        false => "invalid sequence".into(),
+                    },
+                });
             }
         }
     }
@@ -298,11 +784,14 @@ pub fn encode(
     input: &str,
     to_encoding: &str,
     how_process_errors: EncoderTrap,
-) -> Result<Vec<u8>, String> {
-    if let Some(encoder) = encoding_from_whatwg_label(to_encoding) {
-        return Ok(encoder.encode(input, how_process_errors)?);
-    }
-    Err(format!("Encoding '{}' not found", to_encoding))
+) -> Result<Vec<u8>, Error> {
+    let encoder = codec_from_label(to_encoding)
+        .ok_or_else(|| Error::UnknownEncoding(to_encoding.to_string()))?;
+    encoder
+        .encode(input, how_process_errors)
+        .map_err(|_| Error::Encode {
+            encoding: to_encoding.to_string(),
+        })
 }
 
 // Determine if two Unicode range seen next to each other can be considered as suspicious.
@@ -374,13 +863,93 @@ pub(crate) fn get_language_data(language: &Language) -> Result<(&'static str, bo
 // ascii in encodings means windows-1252 codepage with supports diacritis
 // because of this we will check additionally it with is_ascii method
 pub(super) fn is_invalid_chunk(
-    decoded_chunk_result: &Result<String, String>,
+    decoded_chunk_result: &Result<String, Error>,
     encoding_iana: &str,
 ) -> bool {
     decoded_chunk_result.is_err()
         || (encoding_iana == "ascii" && !decoded_chunk_result.as_ref().is_ok_and(|s| s.is_ascii()))
 }
 
+// Offsets of chunks to probe within [start, seq_len), according to sampling strategy.
+// non_ascii_positions provides (sorted) positions of non-ASCII units, it is used only by
+// NonAsciiDense strategy.
+pub(crate) fn sampling_offsets(
+    strategy: SamplingStrategy,
+    start: usize,
+    seq_len: usize,
+    steps: usize,
+    chunk_size: usize,
+    non_ascii_positions: impl FnOnce() -> Vec<usize>,
+) -> Vec<usize> {
+    let evenly_spaced =
+        || -> Vec<usize> { (start..seq_len).step_by((seq_len / steps).max(1)).collect() };
+    if start >= seq_len {
+        return vec![];
+    }
+    let last_offset = seq_len.saturating_sub(chunk_size).max(start);
+
+    let mut offsets: Vec<usize> = match strategy {
+        SamplingStrategy::EvenlySpaced => return evenly_spaced(),
+        SamplingStrategy::NonAsciiDense => {
+            let positions = non_ascii_positions();
+            if positions.is_empty() {
+                return evenly_spaced();
+            }
+            // windows centred on non-ascii units, one per island
+            let mut windows: Vec<usize> = vec![];
+            for position in positions {
+                if windows
+                    .last()
+                    .is_none_or(|&last| position >= last + chunk_size)
+                {
+                    windows.push(
+                        position
+                            .saturating_sub(chunk_size / 2)
+                            .clamp(start, last_offset),
+                    );
+                }
+            }
+            // spread chosen windows over all islands
+            match windows.len() <= steps {
+                true => windows,
+                false => (0..steps)
+                    .map(|i| windows[i * (windows.len() - 1) / (steps - 1).max(1)])
+                    .collect(),
+            }
+        }
+        SamplingStrategy::HeadTail => {
+            let head = (0..steps.div_ceil(2)).map(|i| start + i * chunk_size);
+            let tail = (0..steps / 2).map(|i| seq_len.saturating_sub((i + 1) * chunk_size));
+            head.chain(tail)
+                .map(|offset| offset.clamp(start, last_offset))
+                .collect()
+        }
+        SamplingStrategy::Random { seed } => {
+            let mut rng = SplitMix64(seed);
+            let range = (last_offset - start + 1) as u64;
+            (0..steps)
+                .map(|_| start + (rng.next_u64() % range) as usize)
+                .collect()
+        }
+    };
+    offsets.sort_unstable();
+    offsets.dedup();
+    offsets
+}
+
+// Tiny seeded pseudo-random numbers generator (SplitMix64), good enough for sampling
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
 // Get large datasets
 #[cfg(any(test, feature = "performance"))]
 fn collect_large_sets(dir: &Path) -> Vec<PathBuf> {
@@ -0,0 +1,148 @@
+use charset_normalizer_rs::codecs::{
+    register_codec, similar_encodings, supported_encodings, supported_encodings_count, Codec,
+};
+use charset_normalizer_rs::consts::{IANA_SUPPORTED, IANA_SUPPORTED_COUNT};
+use charset_normalizer_rs::entity::Language;
+use charset_normalizer_rs::error::Error;
+use charset_normalizer_rs::from_bytes;
+use charset_normalizer_rs::utils::{decode, encode, iana_name};
+use encoding::all::WINDOWS_1252;
+use encoding::{DecoderTrap, EncoderTrap, Encoding};
+
+// Cyrillic letters of Bulgarian MIK code page (0x80..=0xBF), other upper half bytes are omitted.
+// Registry is global, so custom codecs are tested in a process of their own.
+struct Mik;
+
+impl Codec for Mik {
+    fn name(&self) -> &'static str {
+        "x-mik"
+    }
+    fn aliases(&self) -> &[&'static str] {
+        &["mik", "bulgaria-pc"]
+    }
+    fn is_multibyte(&self) -> bool {
+        false
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<String, usize> {
+        bytes
+            .iter()
+            .enumerate()
+            .map(|(offset, &b)| match b {
+                0x00..=0x7f => Ok(b as char),
+                0x80..=0xbf => Ok(char::from_u32(0x0410 + (b - 0x80) as u32).unwrap()),
+                _ => Err(offset),
+            })
+            .collect()
+    }
+    fn encode(&self, text: &str) -> Result<Vec<u8>, usize> {
+        text.char_indices()
+            .map(|(offset, ch)| match ch as u32 {
+                0x00..=0x7f => Ok(ch as u8),
+                code @ 0x0410..=0x044f => Ok((code - 0x0410 + 0x80) as u8),
+                _ => Err(offset),
+            })
+            .collect()
+    }
+    fn languages(&self) -> Vec<&'static Language> {
+        vec![&Language::Bulgarian]
+    }
+}
+
+// Windows-1252 with euro sign in place of currency sign, labels are given by test
+struct EuroLatin(&'static str, &'static [&'static str]);
+
+impl Codec for EuroLatin {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+    fn aliases(&self) -> &[&'static str] {
+        self.1
+    }
+    fn is_multibyte(&self) -> bool {
+        false
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<String, usize> {
+        let text = WINDOWS_1252
+            .decode(bytes, DecoderTrap::Strict)
+            .map_err(|_| 0_usize)?;
+        Ok(text.replace('¤', "€"))
+    }
+    fn encode(&self, text: &str) -> Result<Vec<u8>, usize> {
+        WINDOWS_1252
+            .encode(&text.replace('€', "¤"), EncoderTrap::Strict)
+            .map_err(|_| 0_usize)
+    }
+}
+
+#[test]
+fn test_custom_codec() {
+    register_codec(Mik).unwrap();
+    assert!(matches!(
+        register_codec(Mik),
+        Err(Error::DuplicateEncoding(label)) if label == "x-mik"
+    ));
+    assert!(supported_encodings().contains(&"x-mik"));
+    assert_eq!(supported_encodings_count(), *IANA_SUPPORTED_COUNT + 1);
+    assert!(!IANA_SUPPORTED.contains(&"x-mik"));
+    assert_eq!(iana_name("Bulgaria-PC"), Some("x-mik"));
+
+    // labels must be lowercase and unique
+    for (codec, invalid) in [
+        (EuroLatin("X-Euro-Latin", &[]), "X-Euro-Latin"),
+        (EuroLatin("x-euro-latin", &[""]), ""),
+    ] {
+        assert!(matches!(
+            register_codec(codec),
+            Err(Error::InvalidLabel(label)) if label == invalid
+        ));
+    }
+    for (codec, duplicated) in [
+        (EuroLatin("x-euro-latin", &["x-euro-latin"]), "x-euro-latin"),
+        (EuroLatin("x-euro-latin", &["latin1"]), "latin1"),
+    ] {
+        assert!(matches!(
+            register_codec(codec),
+            Err(Error::DuplicateEncoding(label)) if label == duplicated
+        ));
+    }
+    assert_eq!(supported_encodings_count(), *IANA_SUPPORTED_COUNT + 1);
+
+    // single-byte codec is similar to code pages it mostly shares characters with
+    register_codec(EuroLatin("x-euro-latin", &[])).unwrap();
+    assert!(similar_encodings("x-euro-latin").contains(&"windows-1252"));
+    assert!(similar_encodings("windows-1252").contains(&"x-euro-latin"));
+    assert!(!similar_encodings("x-mik").contains(&"windows-1252"));
+
+    // errors point to the first problematic byte or character
+    let text = "Здравей, свят!";
+    let bytes = encode(text, "mik", EncoderTrap::Strict).unwrap();
+    assert_eq!(
+        decode(&bytes, "x-mik", DecoderTrap::Strict, false, false).unwrap(),
+        text
+    );
+    assert!(matches!(
+        decode(b"ab\x80\xf0", "x-mik", DecoderTrap::Strict, false, false),
+        Err(Error::Decode { offset: 3, .. })
+    ));
+    assert_eq!(
+        decode(b"ab\x80\xf0c", "x-mik", DecoderTrap::Replace, false, false).unwrap(),
+        "abА\u{FFFD}c"
+    );
+    assert_eq!(
+        encode("Ёж", "x-mik", EncoderTrap::Replace).unwrap(),
+        b"?\xa6"
+    );
+
+    // encoding takes part in detection
+    let text = "Всички хора се раждат свободни и равни по достойнство и права. \
+        Те са надарени с разум и съвест и следва да се отнасят помежду си в дух на братство.";
+    let result = from_bytes(&encode(text, "x-mik", EncoderTrap::Strict).unwrap(), None).unwrap();
+    let best_guess = result.get_best().unwrap();
+    assert_eq!(best_guess.encoding(), "x-mik");
+    assert_eq!(best_guess.decoded_payload(), Some(text));
+    assert_eq!(best_guess.languages(), vec![&Language::Bulgarian]);
+    assert_eq!(
+        best_guess.encoding_aliases(),
+        vec!["x-mik", "mik", "bulgaria-pc"]
+    );
+}
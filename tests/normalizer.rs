@@ -86,7 +86,9 @@ fn test_cli_multiple_files_with_alternative() {
     ])
     .assert()
     .success()
-    .code(predicate::eq(0));
+    .code(predicate::eq(0))
+    .stdout(predicate::str::contains("\"is_preferred\": false"))
+    .stdout(predicate::str::is_match("\"chaos\": \"[0-9]+\\.[0-9]\"").unwrap());
 }
 
 #[test]
@@ -103,6 +105,20 @@ fn test_cli_multiple_files_with_minimal_output() {
     .code(predicate::eq(0));
 }
 
+#[test]
+fn test_cli_duplicated_files_keep_order() {
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(&[
+        OsString::from("-m"),
+        get_sample_path("sample-arabic-1.txt"),
+        get_sample_path("sample-french.txt"),
+        get_sample_path("sample-arabic-1.txt"),
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::eq("windows-1256\nutf-8\nwindows-1256\n"));
+}
+
 #[test]
 fn test_cli_non_existent_file() {
     let mut cmd = Command::cargo_bin("normalizer").unwrap();
@@ -135,3 +151,19 @@ fn test_cli_force_replace_without_replace() {
     .failure()
     .code(predicate::gt(0));
 }
+
+#[test]
+fn test_cli_multiple_files_with_non_existent_file() {
+    let mut cmd = Command::cargo_bin("normalizer").unwrap();
+    cmd.args(&[
+        OsString::from("-m"),
+        get_sample_path("sample-arabic-1.txt"),
+        get_sample_path("non-exists-file.txt"),
+        get_sample_path("sample-french.txt"),
+    ])
+    .assert()
+    .failure()
+    .code(predicate::eq(1))
+    .stdout(predicate::str::contains("windows-1256"))
+    .stdout(predicate::str::contains("utf-8"));
+}